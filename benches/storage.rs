@@ -0,0 +1,45 @@
+//! Compares how quickly hot buffers can be reused with each [`StorageKind`].
+//!
+//! With a FIFO queue, every take gets the buffer that was returned the longest time ago, so the
+//! pool cycles through all of them and each one has probably been evicted from the CPU cache. With
+//! a LIFO stack, the same buffer keeps getting reused while it's still in the cache.
+//!
+//! Run with `cargo bench --bench storage`.
+
+use dynamic_pooling::{Pool, StorageKind};
+use std::{
+	hint::black_box,
+	time::{Duration, Instant},
+};
+
+/// Enough buffers that all of them together won't fit in the cache, but one of them will.
+const BUFFERS: usize = 64;
+const BUFFER_SIZE: usize = 256 * 1024;
+const ITERATIONS: u32 = 2_000;
+
+fn bench(storage: StorageKind) -> Duration {
+	let pool = Pool::builder()
+		.capacity(BUFFERS)
+		.factory(|| vec![0_u8; BUFFER_SIZE])
+		.reset(|_| {})
+		.storage(storage)
+		.prewarm(BUFFERS)
+		.build()
+		.unwrap();
+
+	let start = Instant::now();
+	for i in 0..ITERATIONS {
+		let mut buffer = pool.take();
+		for byte in buffer.iter_mut() {
+			*byte = byte.wrapping_add(i as u8);
+		}
+		black_box(&buffer);
+	}
+	start.elapsed() / ITERATIONS
+}
+
+fn main() {
+	for storage in [StorageKind::Fifo, StorageKind::LazyFifo, StorageKind::Lifo] {
+		println!("{storage:?}: {:?} per take", bench(storage));
+	}
+}
@@ -0,0 +1,42 @@
+use core::{
+	ops::RangeInclusive,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Adjusts the capacity of a pool to the demand it sees, see
+/// [`PoolBuilder::adaptive`](crate::PoolBuilder::adaptive).
+pub(super) struct Adaptive {
+	pub(super) min: usize,
+	pub(super) max: usize,
+	/// The number of takes between adjustments.
+	window: usize,
+	takes: AtomicUsize,
+	/// The most objects that were in use at once during the current window.
+	high_water: AtomicUsize,
+}
+
+impl Adaptive {
+	pub(super) fn new(bounds: RangeInclusive<usize>, window: usize) -> Self {
+		Self {
+			min: *bounds.start(),
+			max: *bounds.end(),
+			window,
+			takes: AtomicUsize::new(0),
+			high_water: AtomicUsize::new(0),
+		}
+	}
+
+	/// Record that an object was taken while `in_use` objects were in use.
+	///
+	/// At the end of each window, this returns what the capacity should be changed to.
+	pub(super) fn observe(&self, in_use: usize) -> Option<usize> {
+		self.high_water.fetch_max(in_use, Ordering::Relaxed);
+		let takes = self.takes.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+		if !takes.is_multiple_of(self.window) {
+			return None;
+		}
+		// start the next window with what's in use right now
+		let high_water = self.high_water.swap(in_use, Ordering::Relaxed);
+		Some(high_water.clamp(self.min, self.max))
+	}
+}
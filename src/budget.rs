@@ -0,0 +1,318 @@
+#[cfg(feature = "std")]
+use crate::{pool::PoolInner, wait::lock};
+use crate::{reset::Reset, retained::RetainedSize, PoolBuilder};
+#[cfg(feature = "std")]
+use alloc::{
+	sync::{Arc, Weak},
+	vec::Vec,
+};
+use core::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "std")]
+use core::{
+	cmp::Reverse,
+	fmt::{self, Debug},
+};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+/// What to do when a returned object doesn't fit in a pool's
+/// [byte budget](PoolBuilder::max_retained_bytes).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum OverBudget {
+	/// Drop the returned object.
+	#[default]
+	Reject,
+	/// Drop available objects until the returned object fits.
+	#[cfg_attr(
+		feature = "std",
+		doc = "\nObjects in [thread caches](PoolBuilder::thread_cache) can't be evicted."
+	)]
+	Evict,
+}
+
+/// Counts the bytes retained by a pool's available objects.
+pub(super) struct Retained<T> {
+	/// A function pointer so that [`PoolInner`](crate::pool::PoolInner) doesn't need
+	/// `T: RetainedSize`.
+	measure: fn(&T) -> usize,
+	bytes: AtomicUsize,
+	max: usize,
+	over_budget: OverBudget,
+	#[cfg(feature = "std")]
+	shared: Option<Shared<T>>,
+}
+
+/// A [`PoolBudget`] that a pool is attached to.
+#[cfg(feature = "std")]
+struct Shared<T> {
+	budget: PoolBudget,
+	/// A function pointer so that [`PoolInner`] doesn't need `T: Send + 'static`.
+	member: fn(&Arc<PoolInner<T>>) -> Weak<dyn Member>,
+}
+
+impl<T: Reset + RetainedSize> PoolBuilder<T> {
+	/// Keep the total [`RetainedSize`] of available objects at or under `max` bytes.
+	///
+	/// Objects that are returned when there isn't enough room are handled based on `over_budget`.
+	///
+	/// ```
+	/// # use dynamic_pooling::{OverBudget, Pool};
+	/// let pool = Pool::<Vec<u8>>::builder()
+	/// 	.capacity(69)
+	/// 	.max_retained_bytes(4096, OverBudget::Evict)
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let mut foo = pool.take();
+	/// let mut bar = pool.take();
+	/// foo.reserve_exact(3000);
+	/// bar.reserve_exact(3000);
+	/// drop(foo);
+	/// assert_eq!(pool.retained_bytes(), Some(3000));
+	///
+	/// // foo is evicted to make room
+	/// drop(bar);
+	/// assert_eq!(pool.len(), 1);
+	/// assert_eq!(pool.retained_bytes(), Some(3000));
+	/// ```
+	pub fn max_retained_bytes(mut self, max: usize, over_budget: OverBudget) -> Self {
+		let retained = self.retained.get_or_insert_with(Retained::new);
+		retained.max = max;
+		retained.over_budget = over_budget;
+		self
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T: Reset + RetainedSize + Send + 'static> PoolBuilder<T> {
+	/// Count available objects towards a [`PoolBudget`] that's shared with other pools.
+	///
+	/// This can be combined with [`PoolBuilder::max_retained_bytes`], in which case returned
+	/// objects need to fit in both.
+	pub fn budget(mut self, budget: &PoolBudget) -> Self {
+		self.retained.get_or_insert_with(Retained::new).shared = Some(Shared {
+			budget: budget.clone(),
+			member: |pool| Arc::downgrade(pool) as Weak<dyn Member>,
+		});
+		self
+	}
+}
+
+impl<T> Retained<T> {
+	fn new() -> Self
+	where
+		T: RetainedSize,
+	{
+		Self {
+			measure: T::retained_size,
+			bytes: AtomicUsize::new(0),
+			max: usize::MAX,
+			over_budget: OverBudget::Reject,
+			#[cfg(feature = "std")]
+			shared: None,
+		}
+	}
+
+	pub(super) fn measure(&self, object: &T) -> usize {
+		(self.measure)(object)
+	}
+
+	pub(super) fn bytes(&self) -> usize {
+		self.bytes.load(Ordering::Relaxed)
+	}
+
+	/// Count an object's bytes, calling `evict` to make room if allowed. Returns `false` if it
+	/// doesn't fit.
+	pub(super) fn try_add(&self, size: usize, mut evict: impl FnMut() -> bool) -> bool {
+		while !try_add(&self.bytes, size, self.max) {
+			if self.over_budget == OverBudget::Reject || size > self.max || !evict() {
+				return false;
+			}
+		}
+		#[cfg(feature = "std")]
+		if let Some(shared) = &self.shared {
+			if !shared.budget.try_add(size) {
+				self.bytes.fetch_sub(size, Ordering::Relaxed);
+				return false;
+			}
+		}
+		true
+	}
+
+	pub(super) fn remove(&self, size: usize) {
+		self.bytes.fetch_sub(size, Ordering::Relaxed);
+		#[cfg(feature = "std")]
+		if let Some(shared) = &self.shared {
+			shared.budget.inner.bytes.fetch_sub(size, Ordering::Relaxed);
+		}
+	}
+
+	/// Attach a newly built pool to its [`PoolBudget`], if it has one.
+	#[cfg(feature = "std")]
+	pub(super) fn join(&self, pool: &Arc<PoolInner<T>>) {
+		if let Some(shared) = &self.shared {
+			let mut pools = lock(&shared.budget.inner.pools);
+			pools.retain(|pool| pool.strong_count() > 0);
+			pools.push((shared.member)(pool));
+		}
+	}
+}
+
+impl<T> Drop for Retained<T> {
+	fn drop(&mut self) {
+		// the pool was dropped along with its available objects
+		#[cfg(feature = "std")]
+		if let Some(shared) = &self.shared {
+			shared
+				.budget
+				.inner
+				.bytes
+				.fetch_sub(self.bytes(), Ordering::Relaxed);
+		}
+	}
+}
+
+fn try_add(bytes: &AtomicUsize, size: usize, max: usize) -> bool {
+	bytes
+		.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bytes| {
+			bytes.checked_add(size).filter(|bytes| *bytes <= max)
+		})
+		.is_ok()
+}
+
+/// A limit on the total [`RetainedSize`] of available objects across many pools, which can be of
+/// different types.
+///
+/// Pools are attached with [`PoolBuilder::budget`]. Objects that are returned when there isn't
+/// enough room are handled based on the budget's [`OverBudget`], where [`OverBudget::Evict`]
+/// drops available objects from whichever pool is retaining the most bytes.
+///
+/// ```
+/// # use dynamic_pooling::{OverBudget, Pool, PoolBudget};
+/// let budget = PoolBudget::new(4096, OverBudget::Evict);
+/// let bytes = Pool::<Vec<u8>>::builder()
+/// 	.capacity(69)
+/// 	.budget(&budget)
+/// 	.build()
+/// 	.unwrap();
+/// let strings = Pool::<String>::builder()
+/// 	.capacity(69)
+/// 	.budget(&budget)
+/// 	.build()
+/// 	.unwrap();
+///
+/// let mut vec = bytes.take();
+/// vec.reserve_exact(3000);
+/// drop(vec);
+/// assert_eq!(budget.retained_bytes(), 3000);
+///
+/// // the vec is evicted to make room
+/// let mut string = strings.take();
+/// string.reserve_exact(2000);
+/// drop(string);
+/// assert_eq!(bytes.len(), 0);
+/// assert_eq!(strings.len(), 1);
+/// assert_eq!(budget.retained_bytes(), 2000);
+/// ```
+#[cfg(feature = "std")]
+pub struct PoolBudget {
+	inner: Arc<BudgetInner>,
+}
+
+#[cfg(feature = "std")]
+struct BudgetInner {
+	bytes: AtomicUsize,
+	max: usize,
+	over_budget: OverBudget,
+	/// The attached pools, which may have been dropped.
+	pools: Mutex<Vec<Weak<dyn Member>>>,
+}
+
+/// A pool attached to a [`PoolBudget`], without its object type.
+#[cfg(feature = "std")]
+pub(super) trait Member: Send + Sync {
+	fn retained_bytes(&self) -> usize;
+	/// Drop an available object, returning `false` if there weren't any.
+	fn evict_one(&self) -> bool;
+}
+
+#[cfg(feature = "std")]
+impl<T: Send> Member for PoolInner<T> {
+	fn retained_bytes(&self) -> usize {
+		self.retained.as_ref().map_or(0, Retained::bytes)
+	}
+
+	fn evict_one(&self) -> bool {
+		PoolInner::evict_one(self)
+	}
+}
+
+#[cfg(feature = "std")]
+impl PoolBudget {
+	/// Create a new budget of `max` bytes.
+	pub fn new(max: usize, over_budget: OverBudget) -> Self {
+		Self {
+			inner: Arc::new(BudgetInner {
+				bytes: AtomicUsize::new(0),
+				max,
+				over_budget,
+				pools: Mutex::new(Vec::new()),
+			}),
+		}
+	}
+
+	/// The total [`RetainedSize`] of available objects across all attached pools, in bytes.
+	pub fn retained_bytes(&self) -> usize {
+		self.inner.bytes.load(Ordering::Relaxed)
+	}
+
+	/// The maximum number of bytes.
+	pub fn max_bytes(&self) -> usize {
+		self.inner.max
+	}
+
+	/// Count an object's bytes, evicting from the largest pools to make room if allowed.
+	fn try_add(&self, size: usize) -> bool {
+		let inner = &self.inner;
+		while !try_add(&inner.bytes, size, inner.max) {
+			if inner.over_budget == OverBudget::Reject || size > inner.max || !self.evict_largest()
+			{
+				return false;
+			}
+		}
+		true
+	}
+
+	/// Drop an available object from the pool retaining the most bytes that has one.
+	fn evict_largest(&self) -> bool {
+		let mut pools: Vec<_> = {
+			let mut pools = lock(&self.inner.pools);
+			pools.retain(|pool| pool.strong_count() > 0);
+			pools.iter().filter_map(Weak::upgrade).collect()
+		};
+		// evicting drops objects, which can run any code, so the lock can't be held
+		pools.sort_by_cached_key(|pool| Reverse(pool.retained_bytes()));
+		pools.iter().any(|pool| pool.evict_one())
+	}
+}
+
+/// This returns a reference to the same [`PoolBudget`].
+#[cfg(feature = "std")]
+impl Clone for PoolBudget {
+	fn clone(&self) -> Self {
+		Self {
+			inner: Arc::clone(&self.inner),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl Debug for PoolBudget {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("PoolBudget")
+			.field("retained_bytes", &self.retained_bytes())
+			.field("max_bytes", &self.max_bytes())
+			.finish()
+	}
+}
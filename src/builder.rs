@@ -0,0 +1,571 @@
+#[cfg(feature = "std")]
+use crate::cache::ThreadCache;
+use crate::{
+	budget::Retained,
+	pool::{Factory, Measure, Pool, PoolInner, ResetFn},
+	reset::Reset,
+	retained::RetainedSize,
+	shrink::{Oversized, RetainLimit, Shrink},
+	storage::StorageKind,
+	Clock, PoolObserver,
+};
+use alloc::{boxed::Box, string::String, sync::Arc};
+use core::{
+	fmt::{self, Display},
+	ops::RangeInclusive,
+	time::Duration,
+};
+
+/// A builder for configuring a [`Pool`].
+///
+/// ```
+/// # use dynamic_pooling::{Pool, PoolBuilder};
+/// let pool = PoolBuilder::new()
+/// 	.capacity(69)
+/// 	.factory(|| Vec::<u8>::with_capacity(1024))
+/// 	.prewarm(4)
+/// 	.name("buffers")
+/// 	.build()
+/// 	.unwrap();
+///
+/// assert_eq!(pool.len(), 4);
+/// assert_eq!(pool.name(), Some("buffers"));
+/// ```
+pub struct PoolBuilder<T: Reset> {
+	pub(super) capacity: usize,
+	pub(super) factory: Option<Factory<T>>,
+	pub(super) reset: Option<ResetFn<T>>,
+	pub(super) retain_limit: Option<RetainLimit<T>>,
+	pub(super) retained: Option<Retained<T>>,
+	pub(super) admission: Option<Measure<T>>,
+	pub(super) prewarm: usize,
+	pub(super) max_live: Option<usize>,
+	pub(super) name: Option<String>,
+	/// The capacity bounds and window size.
+	pub(super) adaptive: Option<(RangeInclusive<usize>, usize)>,
+	pub(super) storage: StorageKind,
+	pub(super) clock: Option<Box<dyn Clock>>,
+	pub(super) max_uses: Option<usize>,
+	pub(super) max_lifetime: Option<Duration>,
+	pub(super) idle_timeout: Option<Duration>,
+	pub(super) min_idle: usize,
+	pub(super) track_stats: bool,
+	pub(super) observer: Option<Box<dyn PoolObserver>>,
+	/// Overrides the storage with size classes, using this to get an object's capacity.
+	pub(super) size_classes: Option<fn(&T) -> usize>,
+	#[cfg(feature = "std")]
+	pub(super) thread_cache: Option<Box<dyn ThreadCache<T>>>,
+}
+
+impl<T: Default + Reset> Pool<T> {
+	/// Create a [`PoolBuilder`] that creates new objects with [`Default`].
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<String>::builder().capacity(69).build().unwrap();
+	/// assert_eq!(pool.capacity(), 69);
+	/// ```
+	pub fn builder() -> PoolBuilder<T> {
+		PoolBuilder {
+			factory: Some(Factory::Fn(T::default)),
+			..PoolBuilder::new()
+		}
+	}
+}
+
+impl<T: Reset> PoolBuilder<T> {
+	/// Create a new builder with no factory.
+	///
+	/// A factory must be set with [`PoolBuilder::factory`] before building. See [`Pool::builder`]
+	/// for types that implement [`Default`].
+	pub fn new() -> Self {
+		Self {
+			capacity: 0,
+			factory: None,
+			reset: None,
+			retain_limit: None,
+			retained: None,
+			admission: None,
+			prewarm: 0,
+			max_live: None,
+			name: None,
+			adaptive: None,
+			storage: StorageKind::Fifo,
+			clock: None,
+			max_uses: None,
+			max_lifetime: None,
+			idle_timeout: None,
+			min_idle: 0,
+			track_stats: false,
+			observer: None,
+			size_classes: None,
+			#[cfg(feature = "std")]
+			thread_cache: None,
+		}
+	}
+
+	/// Set the maximum number of available objects the pool can hold.
+	///
+	/// This is required unless the pool is [adaptive](PoolBuilder::adaptive). Note: The capacity
+	/// will be fully allocated unless a different [`StorageKind`] is used.
+	pub fn capacity(mut self, capacity: usize) -> Self {
+		self.capacity = capacity;
+		self
+	}
+
+	/// Set the function used to create new objects.
+	pub fn factory<F>(mut self, factory: F) -> Self
+	where
+		F: Fn() -> T + Send + Sync + 'static,
+	{
+		self.factory = Some(Factory::Boxed(Box::new(factory)));
+		self
+	}
+
+	/// Reset objects with this instead of their [`Reset`] implementation.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<Vec<u8>>::builder()
+	/// 	.capacity(69)
+	/// 	.reset(|vec| {
+	/// 		vec.clear();
+	/// 		vec.shrink_to(1024);
+	/// 	})
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let mut vec = pool.take();
+	/// vec.reserve(4096);
+	/// drop(vec);
+	///
+	/// assert!(pool.take().capacity() < 4096);
+	/// ```
+	///
+	/// If resetting an object panics, it's dropped and no longer counts as a
+	/// [live object](Pool::live), like any other [discarded](crate::DiscardReason) object.
+	///
+	/// ```
+	/// # use dynamic_pooling::{PoolBuilder, Pooled};
+	/// # use std::panic::{self, AssertUnwindSafe};
+	/// let pool = PoolBuilder::new()
+	/// 	.capacity(69)
+	/// 	.max_live(1)
+	/// 	.factory(String::new)
+	/// 	.reset(|_| panic!("oops"))
+	/// 	.track_stats()
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let result = panic::catch_unwind(AssertUnwindSafe(|| drop(pool.take())));
+	/// assert!(result.is_err());
+	/// assert_eq!(pool.live(), 0);
+	/// assert_eq!(pool.stats().unwrap().discards, 1);
+	/// let foo = pool.try_take_bounded().unwrap();
+	/// # Pooled::detach(foo);
+	/// ```
+	pub fn reset<F>(mut self, reset: F) -> Self
+	where
+		F: Fn(&mut T) + Send + Sync + 'static,
+	{
+		self.reset = Some(Box::new(reset));
+		self
+	}
+
+	/// When an object is returned to a full pool, evict the smallest available object according to
+	/// `size` if the returned object is larger, and keep the returned object instead.
+	///
+	/// Without this, objects returned to a full pool are always dropped. To keep returns cheap,
+	/// only the next few objects that would be taken from the storage are compared, so the evicted
+	/// object is the smallest of those rather than of every available object. See
+	/// [`PoolBuilder::prefer_larger`] to use [`RetainedSize`].
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<Vec<u8>>::builder()
+	/// 	.capacity(2)
+	/// 	.prefer_larger_by(|vec| vec.capacity())
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let [mut huge, small, mut big] = [pool.take(), pool.take(), pool.take()];
+	/// huge.reserve(8192);
+	/// big.reserve(1024);
+	/// drop((huge, small, big));
+	///
+	/// // the small vec was evicted
+	/// assert_eq!(pool.len(), 2);
+	/// assert!(pool.take().capacity() >= 8192);
+	/// assert!(pool.take().capacity() >= 1024);
+	/// ```
+	pub fn prefer_larger_by<F>(mut self, size: F) -> Self
+	where
+		F: Fn(&T) -> usize + Send + Sync + 'static,
+	{
+		self.admission = Some(Measure::Boxed(Box::new(size)));
+		self
+	}
+
+	/// Fill the pool with this many new objects when it's built.
+	pub fn prewarm(mut self, count: usize) -> Self {
+		self.prewarm = count;
+		self
+	}
+
+	/// Set the maximum number of live objects, both available and in use.
+	///
+	/// Once reached, [`Pool::take`] panics and [`Pool::try_take_bounded`] returns an error instead
+	/// of creating a new object.
+	pub fn max_live(mut self, max_live: usize) -> Self {
+		self.max_live = Some(max_live);
+		self
+	}
+
+	/// Let the capacity grow and shrink with demand, staying within `bounds`.
+	///
+	/// After every `window` takes, the capacity is set to the most objects that were in use at once
+	/// during that window, and available objects that no longer fit are dropped. If no capacity is
+	/// set, the pool starts at the lower bound.
+	///
+	/// Note: Only the starting capacity will be allocated up front, and only for some
+	/// [`StorageKind`]s.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<String>::builder().adaptive(1..=100, 4).build().unwrap();
+	/// assert_eq!(pool.capacity(), 1);
+	///
+	/// // use 3 objects at once
+	/// let objects = [pool.take(), pool.take(), pool.take()];
+	/// drop(objects);
+	/// assert_eq!(pool.len(), 1);
+	///
+	/// // the 4th take ends the window
+	/// drop(pool.take());
+	/// assert_eq!(pool.capacity(), 3);
+	///
+	/// // demand falls, so the capacity shrinks
+	/// for _ in 0..4 {
+	/// 	drop(pool.take());
+	/// }
+	/// assert_eq!(pool.capacity(), 1);
+	/// ```
+	pub fn adaptive(mut self, bounds: RangeInclusive<usize>, window: usize) -> Self {
+		self.adaptive = Some((bounds, window));
+		self
+	}
+
+	/// Set how available objects are stored. Defaults to [`StorageKind::Fifo`].
+	pub fn storage(mut self, storage: StorageKind) -> Self {
+		self.storage = storage;
+		self
+	}
+
+	/// Only allocate room for available objects as they're returned, instead of allocating the
+	/// full capacity up front.
+	///
+	/// This is the same as `.storage(StorageKind::LazyFifo)`. It makes large capacities free until
+	/// they're used, but returning objects is slightly slower.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<Vec<u8>>::builder()
+	/// 	.capacity(1_000_000)
+	/// 	.lazy()
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// drop(pool.take());
+	/// assert_eq!(pool.len(), 1);
+	/// ```
+	pub fn lazy(self) -> Self {
+		self.storage(StorageKind::LazyFifo)
+	}
+
+	/// Drop objects instead of returning them to the pool once they've been handed out this many
+	/// times.
+	///
+	/// This is useful for objects that slowly build up state that [`Reset`] can't clear.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<String>::builder().capacity(69).max_uses(2).build().unwrap();
+	///
+	/// drop(pool.take());
+	/// drop(pool.take());
+	/// assert_eq!(pool.len(), 0);
+	/// ```
+	pub fn max_uses(mut self, max_uses: usize) -> Self {
+		self.max_uses = Some(max_uses);
+		self
+	}
+
+	/// Drop objects instead of returning them to the pool once this long has passed since they
+	/// were created.
+	///
+	/// ```
+	/// # use dynamic_pooling::{ManualClock, Pool};
+	/// # use std::time::Duration;
+	/// let clock = ManualClock::new();
+	/// let pool = Pool::<String>::builder()
+	/// 	.capacity(69)
+	/// 	.clock(clock.clone())
+	/// 	.max_lifetime(Duration::from_secs(60))
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let old = pool.take();
+	/// clock.advance(Duration::from_secs(30));
+	/// let young = pool.take();
+	/// clock.advance(Duration::from_secs(30));
+	/// assert_eq!(pool.live(), 2);
+	///
+	/// drop((old, young));
+	/// assert_eq!(pool.len(), 1);
+	/// assert_eq!(pool.live(), 1);
+	/// ```
+	pub fn max_lifetime(mut self, max_lifetime: Duration) -> Self {
+		self.max_lifetime = Some(max_lifetime);
+		self
+	}
+
+	/// Set the clock used to tell how long objects have been available.
+	#[cfg_attr(feature = "std", doc = "Defaults to [`StdClock`](crate::StdClock).")]
+	pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+		self.clock = Some(Box::new(clock));
+		self
+	}
+
+	/// Drop objects that have been available for at least `timeout` instead of handing them out.
+	///
+	/// This is checked when objects are taken. See [`Pool::evict_idle`] to drop them sooner.
+	///
+	/// ```
+	/// # use dynamic_pooling::{ManualClock, Pool};
+	/// # use std::time::Duration;
+	/// let clock = ManualClock::new();
+	/// let pool = Pool::<String>::builder()
+	/// 	.capacity(69)
+	/// 	.clock(clock.clone())
+	/// 	.idle_timeout(Duration::from_secs(60))
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// drop(pool.take());
+	/// clock.advance(Duration::from_secs(60));
+	///
+	/// assert!(pool.try_take().is_none());
+	/// assert_eq!(pool.live(), 0);
+	/// ```
+	pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+		self.idle_timeout = Some(timeout);
+		self
+	}
+
+	/// Set the number of available objects that a reaper won't drop. Defaults to `0`.
+	#[cfg_attr(
+		feature = "std",
+		doc = "\nReapers are started with [`Pool::spawn_reaper`]."
+	)]
+	pub fn min_idle(mut self, min_idle: usize) -> Self {
+		self.min_idle = min_idle;
+		self
+	}
+
+	/// Count what happens to the pool's objects, which can be read with [`Pool::stats`].
+	pub fn track_stats(mut self) -> Self {
+		self.track_stats = true;
+		self
+	}
+
+	/// Set a [`PoolObserver`] to be told what happens to the pool's objects.
+	pub fn observer(mut self, observer: impl PoolObserver + 'static) -> Self {
+		self.observer = Some(Box::new(observer));
+		self
+	}
+
+	/// Set a name for the pool, which is shown in its [`Debug`](core::fmt::Debug) output.
+	///
+	/// With the `metrics` feature, this is used to label the pool's metrics instead of its type
+	/// name. Pools with the same label share their metrics, which add up all of their values.
+	pub fn name(mut self, name: impl Into<String>) -> Self {
+		self.name = Some(name.into());
+		self
+	}
+
+	/// Build the pool.
+	///
+	/// ```
+	/// # use dynamic_pooling::{BuildError, Pool};
+	/// let result = Pool::<String>::builder().capacity(1).prewarm(2).build();
+	/// assert_eq!(result.err(), Some(BuildError::PrewarmExceedsCapacity));
+	/// ```
+	pub fn build(mut self) -> Result<Pool<T>, BuildError> {
+		if let Some((bounds, window)) = &self.adaptive {
+			if bounds.is_empty() {
+				return Err(BuildError::InvalidBounds);
+			}
+			if *bounds.start() == 0 {
+				return Err(BuildError::ZeroCapacity);
+			}
+			if *window == 0 {
+				return Err(BuildError::ZeroWindow);
+			}
+			if self.capacity == 0 {
+				self.capacity = *bounds.start();
+			} else if !bounds.contains(&self.capacity) {
+				return Err(BuildError::CapacityOutOfBounds);
+			}
+		}
+		if self.capacity == 0 {
+			return Err(BuildError::ZeroCapacity);
+		}
+		if self.factory.is_none() {
+			return Err(BuildError::MissingFactory);
+		}
+		if self.prewarm > self.capacity {
+			return Err(BuildError::PrewarmExceedsCapacity);
+		}
+		if let Some(max_live) = self.max_live {
+			if max_live == 0 {
+				return Err(BuildError::ZeroMaxLive);
+			}
+			if self.prewarm > max_live {
+				return Err(BuildError::PrewarmExceedsMaxLive);
+			}
+		}
+		if self.max_uses == Some(0) {
+			return Err(BuildError::ZeroMaxUses);
+		}
+		#[cfg(not(feature = "std"))]
+		if self.clock.is_none() && (self.idle_timeout.is_some() || self.max_lifetime.is_some()) {
+			return Err(BuildError::MissingClock);
+		}
+		#[cfg(feature = "std")]
+		if self.storage == StorageKind::Sharded(0) {
+			return Err(BuildError::ZeroShards);
+		}
+		#[cfg(feature = "std")]
+		if let Some(thread_cache) = &self.thread_cache {
+			if thread_cache.size() == 0 {
+				return Err(BuildError::ZeroThreadCache);
+			}
+			if self.max_live.is_some() {
+				return Err(BuildError::ThreadCacheWithMaxLive);
+			}
+		}
+
+		let inner = Arc::new(PoolInner::new(self));
+		#[cfg(feature = "std")]
+		if let Some(retained) = &inner.retained {
+			retained.join(&inner);
+		}
+		Ok(Pool { inner })
+	}
+}
+
+impl<T: Reset + RetainedSize> PoolBuilder<T> {
+	/// When an object is returned to a full pool, evict an available object with a smaller
+	/// [`RetainedSize`] if there is one, and keep the returned object instead.
+	///
+	/// This makes the pool hold on to objects that have already grown. Like
+	/// [`PoolBuilder::prefer_larger_by`], which can measure objects some other way, only a few
+	/// available objects are compared.
+	pub fn prefer_larger(mut self) -> Self {
+		self.admission = Some(Measure::Fn(T::retained_size));
+		self
+	}
+}
+
+impl<T: Reset + Shrink> PoolBuilder<T> {
+	/// Limit how much capacity returned objects can keep, so that one unusually large use doesn't
+	/// hold on to memory forever.
+	///
+	/// This is checked after objects are reset, and objects with a larger [capacity](crate::Capacity)
+	/// are shrunk or dropped depending on `oversized`.
+	///
+	/// ```
+	/// # use dynamic_pooling::{Oversized, Pool};
+	/// let pool = Pool::<Vec<u8>>::builder()
+	/// 	.capacity(69)
+	/// 	.max_retained_capacity(1024, Oversized::Discard)
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let mut vec = pool.take();
+	/// vec.reserve(4096);
+	/// drop(vec);
+	/// assert_eq!(pool.len(), 0);
+	/// ```
+	pub fn max_retained_capacity(mut self, max: usize, oversized: Oversized) -> Self {
+		self.retain_limit = Some(RetainLimit {
+			max,
+			oversized,
+			capacity: T::capacity,
+			shrink: T::shrink_to,
+		});
+		self
+	}
+}
+
+impl<T: Reset> Default for PoolBuilder<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// An error returned by [`PoolBuilder::build`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BuildError {
+	/// The capacity was `0` or wasn't set.
+	ZeroCapacity,
+	/// No factory was set.
+	MissingFactory,
+	/// More objects were prewarmed than the pool can hold.
+	PrewarmExceedsCapacity,
+	/// The maximum number of live objects was `0`.
+	ZeroMaxLive,
+	/// More objects were prewarmed than the maximum number of live objects.
+	PrewarmExceedsMaxLive,
+	/// The adaptive capacity bounds were empty.
+	InvalidBounds,
+	/// The adaptive window was `0`.
+	ZeroWindow,
+	/// The capacity was outside of the adaptive capacity bounds.
+	CapacityOutOfBounds,
+	/// The thread cache size was `0`.
+	ZeroThreadCache,
+	/// A thread cache was used with a maximum number of live objects.
+	ThreadCacheWithMaxLive,
+	/// The number of shards was `0`.
+	ZeroShards,
+	/// The maximum number of uses was `0`.
+	ZeroMaxUses,
+	/// An idle timeout or maximum lifetime was set without a clock, and the `std` feature is
+	/// disabled.
+	MissingClock,
+}
+
+impl Display for BuildError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Self::ZeroCapacity => "capacity must be more than 0",
+			Self::MissingFactory => "a factory must be set",
+			Self::PrewarmExceedsCapacity => "cannot prewarm more objects than the capacity",
+			Self::ZeroMaxLive => "max live objects must be more than 0",
+			Self::PrewarmExceedsMaxLive => "cannot prewarm more objects than the max live objects",
+			Self::InvalidBounds => "capacity bounds must not be empty",
+			Self::ZeroWindow => "window must be more than 0",
+			Self::CapacityOutOfBounds => "capacity must be within the capacity bounds",
+			Self::ZeroThreadCache => "thread cache size must be more than 0",
+			Self::ThreadCacheWithMaxLive => "thread caches cannot be used with max live objects",
+			Self::ZeroShards => "number of shards must be more than 0",
+			Self::ZeroMaxUses => "max uses must be more than 0",
+			Self::MissingClock => "a clock must be set to use idle timeouts or max lifetimes",
+		})
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
@@ -0,0 +1,210 @@
+use crate::{meta::Entry, pool::PoolInner, reset::Reset, storage::Storage, PoolBuilder};
+use alloc::{
+	boxed::Box,
+	sync::{Arc, Weak},
+	vec::Vec,
+};
+use core::{
+	any::Any,
+	cell::RefCell,
+	iter, mem,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+
+thread_local! {
+	/// This thread's magazine for every pool with a thread cache that it has used.
+	static MAGAZINES: RefCell<Vec<Box<dyn AnyMagazine>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Used to tell pools apart, since their addresses can be reused.
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A pool's per-thread caches, see [`PoolBuilder::thread_cache`].
+///
+/// This is a trait so that [`PoolInner`] doesn't need `T: Send + 'static`.
+pub(super) trait ThreadCache<T>: Send + Sync {
+	/// The maximum number of objects each thread can cache.
+	fn size(&self) -> usize;
+	/// Take an object from this thread's cache, refilling it from the pool's storage if it's empty.
+	fn pop(&self, pool: &Arc<PoolInner<T>>) -> Option<Entry<T>>;
+	/// Put an object in this thread's cache, making room by moving objects to the pool's storage.
+	///
+	/// The object is given back if the cache can't be used right now.
+	fn push(&self, pool: &Arc<PoolInner<T>>, idle: Entry<T>) -> Result<(), Entry<T>>;
+}
+
+struct Magazines {
+	id: usize,
+	size: usize,
+}
+
+/// One thread's cache for one pool.
+struct Magazine<T> {
+	id: usize,
+	pool: Weak<PoolInner<T>>,
+	objects: Vec<Entry<T>>,
+}
+
+trait AnyMagazine {
+	fn id(&self) -> usize;
+	/// Whether the pool was dropped.
+	fn is_orphaned(&self) -> bool;
+	fn as_any(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Reset + Send + 'static> PoolBuilder<T> {
+	/// Give each thread a cache of up to `size` available objects, so that most takes and returns
+	/// don't touch the pool's shared storage.
+	///
+	/// Objects move between a thread's cache and the shared storage in batches, and whatever is
+	/// left in a thread's cache is moved back when the thread exits. If the pool is dropped, each
+	/// thread drops its cached objects the next time it uses any pool's thread cache, or when it
+	/// exits. Cached objects count towards
+	/// [`Pool::len`](crate::Pool::len), but [`Pool::shrink_to`](crate::Pool::shrink_to) and
+	/// friends can only drop objects from the shared storage.
+	///
+	/// This can't be used with [`PoolBuilder::max_live`], since a thread that's waiting for an
+	/// object couldn't take one from another thread's cache.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// # use std::thread;
+	/// let pool = Pool::<String>::builder()
+	/// 	.capacity(69)
+	/// 	.thread_cache(16)
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let other_pool = pool.clone();
+	/// thread::spawn(move || {
+	/// 	let objects = [other_pool.take(), other_pool.take(), other_pool.take()];
+	/// 	// returned to this thread's cache
+	/// 	drop(objects);
+	/// })
+	/// .join()
+	/// .unwrap();
+	///
+	/// // the thread exited, so its cache was moved back
+	/// assert_eq!(pool.len(), 3);
+	/// assert!(pool.try_take().is_some());
+	/// ```
+	pub fn thread_cache(mut self, size: usize) -> Self {
+		self.thread_cache = Some(Box::new(Magazines {
+			id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+			size,
+		}));
+		self
+	}
+}
+
+impl Magazines {
+	/// The number of objects to move at once.
+	fn batch(&self) -> usize {
+		(self.size / 2).max(1)
+	}
+
+	/// Use this thread's magazine, returning [`None`] if that isn't possible right now.
+	fn with<T, R>(
+		&self,
+		pool: &Arc<PoolInner<T>>,
+		f: impl FnOnce(&mut Magazine<T>) -> R,
+	) -> Option<R>
+	where
+		T: Send + 'static,
+	{
+		let mut orphans = Vec::new();
+		let result = MAGAZINES
+			.try_with(|magazines| {
+				// this fails if an object's drop code uses the cache while we're in here
+				let mut magazines = magazines.try_borrow_mut().ok()?;
+				// free the objects of pools that were dropped
+				if magazines.iter().any(|magazine| magazine.is_orphaned()) {
+					let (alive, dead) = mem::take(&mut *magazines)
+						.into_iter()
+						.partition(|magazine| !magazine.is_orphaned());
+					*magazines = alive;
+					orphans = dead;
+				}
+				let index = match magazines
+					.iter()
+					.position(|magazine| magazine.id() == self.id)
+				{
+					Some(index) => index,
+					None => {
+						magazines.push(Box::new(Magazine {
+							id: self.id,
+							pool: Arc::downgrade(pool),
+							objects: Vec::with_capacity(self.size),
+						}));
+						magazines.len() - 1
+					},
+				};
+				let magazine = magazines[index]
+					.as_any()
+					.downcast_mut::<Magazine<T>>()
+					.expect("ids are unique");
+				Some(f(magazine))
+			})
+			.ok()
+			.flatten();
+		// dropping objects can run any code, so wait until the magazines aren't borrowed
+		drop(orphans);
+		result
+	}
+}
+
+impl<T: Send + 'static> ThreadCache<T> for Magazines {
+	fn size(&self) -> usize {
+		self.size
+	}
+
+	fn pop(&self, pool: &Arc<PoolInner<T>>) -> Option<Entry<T>> {
+		self.with(pool, |magazine| {
+			if magazine.objects.is_empty() {
+				let batch = iter::from_fn(|| pool.storage.pop()).take(self.batch());
+				magazine.objects.extend(batch);
+			}
+			magazine.objects.pop()
+		})
+		.flatten()
+	}
+
+	fn push(&self, pool: &Arc<PoolInner<T>>, idle: Entry<T>) -> Result<(), Entry<T>> {
+		let mut idle = Some(idle);
+		self.with(pool, |magazine| {
+			if magazine.objects.len() >= self.size {
+				// the oldest objects are the least likely to be in the cpu cache
+				for idle in magazine.objects.drain(..self.batch()) {
+					pool.storage.push(idle);
+				}
+			}
+			magazine.objects.extend(idle.take());
+		});
+		idle.map_or(Ok(()), Err)
+	}
+}
+
+impl<T: 'static> AnyMagazine for Magazine<T> {
+	fn id(&self) -> usize {
+		self.id
+	}
+
+	fn is_orphaned(&self) -> bool {
+		self.pool.strong_count() == 0
+	}
+
+	fn as_any(&mut self) -> &mut dyn Any {
+		self
+	}
+}
+
+impl<T> Drop for Magazine<T> {
+	fn drop(&mut self) {
+		// the objects are still counted as available, so they just need to be moved
+		if let Some(pool) = self.pool.upgrade() {
+			for idle in self.objects.drain(..) {
+				pool.storage.push(idle);
+			}
+		}
+	}
+}
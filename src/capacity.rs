@@ -0,0 +1,134 @@
+use alloc::{
+	collections::{BinaryHeap, VecDeque},
+	string::String,
+	vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+	collections::{HashMap, HashSet},
+	ffi::OsString,
+	hash::{BuildHasher, Hash},
+	path::PathBuf,
+};
+
+/// Trait for types with a capacity, like collections.
+///
+/// This is used by [`SizedPool`](crate::SizedPool) to hand out objects that are already big
+/// enough.
+///
+/// ```
+/// # use dynamic_pooling::Capacity;
+/// struct Buffer {
+/// 	bytes: Vec<u8>,
+/// }
+///
+/// impl Capacity for Buffer {
+/// 	fn capacity(&self) -> usize {
+/// 		self.bytes.capacity()
+/// 	}
+///
+/// 	fn with_capacity(capacity: usize) -> Self {
+/// 		Self {
+/// 			bytes: Vec::with_capacity(capacity),
+/// 		}
+/// 	}
+/// }
+/// ```
+pub trait Capacity {
+	/// The number of items this can hold without reallocating.
+	fn capacity(&self) -> usize;
+	/// Create an empty value that can hold at least `capacity` items without reallocating.
+	fn with_capacity(capacity: usize) -> Self;
+}
+
+impl<T> Capacity for Vec<T> {
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity(capacity)
+	}
+}
+
+impl Capacity for String {
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity(capacity)
+	}
+}
+
+impl<T> Capacity for VecDeque<T> {
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity(capacity)
+	}
+}
+
+impl<T: Ord> Capacity for BinaryHeap<T> {
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity(capacity)
+	}
+}
+
+#[cfg(feature = "std")]
+impl Capacity for PathBuf {
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity(capacity)
+	}
+}
+
+#[cfg(feature = "std")]
+impl Capacity for OsString {
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity(capacity)
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> Capacity for HashMap<K, V, S>
+where
+	K: Eq + Hash,
+	S: BuildHasher + Default,
+{
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity_and_hasher(capacity, S::default())
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T, S> Capacity for HashSet<T, S>
+where
+	T: Eq + Hash,
+	S: BuildHasher + Default,
+{
+	fn capacity(&self) -> usize {
+		self.capacity()
+	}
+
+	fn with_capacity(capacity: usize) -> Self {
+		Self::with_capacity_and_hasher(capacity, S::default())
+	}
+}
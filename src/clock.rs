@@ -0,0 +1,77 @@
+use alloc::sync::Arc;
+use core::time::Duration;
+use crossbeam_utils::atomic::AtomicCell;
+#[cfg(feature = "std")]
+use std::{sync::OnceLock, time::Instant};
+
+/// A source of time for a pool, see [`PoolBuilder::clock`](crate::PoolBuilder::clock).
+///
+/// Without the `std` feature, the default clock never advances, so building a pool with
+/// time-based features like [idle timeouts](crate::PoolBuilder::idle_timeout) fails with
+/// [`BuildError::MissingClock`](crate::BuildError::MissingClock) unless a clock is set.
+#[cfg_attr(
+	feature = "std",
+	doc = "\nOtherwise, pools use [`StdClock`] by default."
+)]
+pub trait Clock: Send + Sync {
+	/// The time since some fixed point, which must never go backwards.
+	fn now(&self) -> Duration;
+}
+
+/// A [`Clock`] that uses [`Instant`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StdClock;
+
+#[cfg(feature = "std")]
+impl Clock for StdClock {
+	fn now(&self) -> Duration {
+		static START: OnceLock<Instant> = OnceLock::new();
+		START.get_or_init(Instant::now).elapsed()
+	}
+}
+
+/// A [`Clock`] that only moves when told to, which is useful for tests.
+///
+/// Clones share the same time.
+///
+/// ```
+/// # use dynamic_pooling::{Clock, ManualClock};
+/// # use std::time::Duration;
+/// let clock = ManualClock::new();
+/// let other_clock = clock.clone();
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(other_clock.now(), Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+	/// In nanoseconds. This falls back to a lock on targets without 64-bit atomics.
+	now: Arc<AtomicCell<u64>>,
+}
+
+impl ManualClock {
+	/// Create a new clock starting at zero.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Move the clock forward.
+	pub fn advance(&self, duration: Duration) {
+		self.now.fetch_add(nanos(duration));
+	}
+
+	/// Set the current time, which must not be earlier than before.
+	pub fn set(&self, now: Duration) {
+		self.now.store(nanos(now));
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> Duration {
+		Duration::from_nanos(self.now.load())
+	}
+}
+
+fn nanos(duration: Duration) -> u64 {
+	duration.as_nanos().try_into().unwrap_or(u64::MAX)
+}
@@ -0,0 +1,340 @@
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+#[cfg(feature = "tracing")]
+use crate::tracing::Tracing;
+use alloc::{boxed::Box, sync::Arc};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Callbacks for what happens to a pool's objects, see
+/// [`PoolBuilder::observer`](crate::PoolBuilder::observer).
+///
+/// Every method does nothing by default. They're called on the thread that caused the event, so
+/// they should be quick.
+///
+/// ```
+/// # use dynamic_pooling::{DiscardReason, Pool, PoolObserver};
+/// # use std::sync::{
+/// # 	atomic::{AtomicUsize, Ordering},
+/// # 	Arc,
+/// # };
+/// struct Rejections(Arc<AtomicUsize>);
+///
+/// impl PoolObserver for Rejections {
+/// 	fn on_discard(&self, reason: DiscardReason) {
+/// 		if reason == DiscardReason::Full {
+/// 			self.0.fetch_add(1, Ordering::Relaxed);
+/// 		}
+/// 	}
+/// }
+///
+/// let rejections = Arc::new(AtomicUsize::new(0));
+/// let pool = Pool::<String>::builder()
+/// 	.capacity(1)
+/// 	.observer(Rejections(Arc::clone(&rejections)))
+/// 	.build()
+/// 	.unwrap();
+///
+/// drop([pool.take(), pool.take()]);
+/// assert_eq!(rejections.load(Ordering::Relaxed), 1);
+/// ```
+pub trait PoolObserver: Send + Sync {
+	/// An object was created, either because none were available or to
+	/// [prewarm](crate::PoolBuilder::prewarm) the pool.
+	fn on_create(&self) {}
+
+	/// An object was taken from the pool or [attached](crate::Pool::attach) to it.
+	fn on_take(&self) {}
+
+	/// An object was returned and kept by the pool.
+	fn on_return(&self) {}
+
+	/// An object was dropped instead of being kept by the pool.
+	fn on_discard(&self, reason: DiscardReason) {
+		let _ = reason;
+	}
+
+	/// An object was dropped after its pool.
+	fn on_orphan(&self) {}
+}
+
+/// Why an object was dropped instead of being kept by its pool, see
+/// [`PoolObserver::on_discard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiscardReason {
+	/// The pool was full or over its byte budget when the object was returned.
+	Full,
+	/// The object reached the pool's [maximum uses](crate::PoolBuilder::max_uses).
+	MaxUses,
+	/// The object reached the pool's [maximum lifetime](crate::PoolBuilder::max_lifetime).
+	MaxLifetime,
+	/// The object was over the pool's
+	/// [maximum retained capacity](crate::PoolBuilder::max_retained_capacity).
+	Oversized,
+	/// The object was available for too long.
+	Idle,
+	/// The object was dropped to make room, or because the pool shrank.
+	Evicted,
+	/// The object panicked while being [reset](crate::Reset).
+	ResetPanicked,
+}
+
+impl DiscardReason {
+	#[cfg(feature = "metrics")]
+	pub(super) const ALL: [Self; 7] = [
+		Self::Full,
+		Self::MaxUses,
+		Self::MaxLifetime,
+		Self::Oversized,
+		Self::Idle,
+		Self::Evicted,
+		Self::ResetPanicked,
+	];
+
+	#[cfg(feature = "metrics")]
+	pub(super) fn as_str(self) -> &'static str {
+		match self {
+			Self::Full => "full",
+			Self::MaxUses => "max_uses",
+			Self::MaxLifetime => "max_lifetime",
+			Self::Oversized => "oversized",
+			Self::Idle => "idle",
+			Self::Evicted => "evicted",
+			Self::ResetPanicked => "reset_panicked",
+		}
+	}
+}
+
+/// Keeps track of what happens to a pool and its objects.
+///
+/// This is shared with objects, so that they can report being dropped after their pool.
+pub(super) struct Events {
+	pub(super) stats: Option<Stats>,
+	#[cfg(feature = "metrics")]
+	pub(super) metrics: Metrics,
+	#[cfg(feature = "tracing")]
+	tracing: Tracing,
+	observer: Option<Box<dyn PoolObserver>>,
+}
+
+impl Events {
+	/// Returns [`None`] if nothing needs to keep track.
+	#[cfg_attr(
+		not(any(feature = "metrics", feature = "tracing")),
+		allow(unused_variables)
+	)]
+	pub(super) fn new(
+		track_stats: bool,
+		observer: Option<Box<dyn PoolObserver>>,
+		name: Option<&str>,
+		type_name: &'static str,
+	) -> Option<Arc<Self>> {
+		let enabled = track_stats
+			|| observer.is_some()
+			|| cfg!(any(feature = "metrics", feature = "tracing"));
+		enabled.then(|| {
+			Arc::new(Self {
+				stats: track_stats.then(Stats::default),
+				#[cfg(feature = "metrics")]
+				metrics: Metrics::new(name.unwrap_or(type_name)),
+				#[cfg(feature = "tracing")]
+				tracing: Tracing::new(name, type_name),
+				observer,
+			})
+		})
+	}
+
+	pub(super) fn created(&self) {
+		if let Some(observer) = &self.observer {
+			observer.on_create();
+		}
+	}
+
+	pub(super) fn taken(&self, fresh: bool, in_use: usize) {
+		if fresh {
+			self.created();
+		}
+		if let Some(observer) = &self.observer {
+			observer.on_take();
+		}
+		#[cfg(feature = "metrics")]
+		self.metrics.taken(fresh);
+		#[cfg(feature = "tracing")]
+		if fresh {
+			self.tracing.created();
+		}
+		if let Some(stats) = &self.stats {
+			let counter = if fresh { &stats.misses } else { &stats.hits };
+			counter.fetch_add(1, Ordering::Relaxed);
+			stats.peak_in_use.fetch_max(in_use, Ordering::Relaxed);
+		}
+	}
+
+	pub(super) fn attached(&self, in_use: usize) {
+		if let Some(observer) = &self.observer {
+			observer.on_take();
+		}
+		if let Some(stats) = &self.stats {
+			stats.peak_in_use.fetch_max(in_use, Ordering::Relaxed);
+		}
+	}
+
+	pub(super) fn returned(&self) {
+		if let Some(observer) = &self.observer {
+			observer.on_return();
+		}
+		if let Some(stats) = &self.stats {
+			stats.returns.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	pub(super) fn discarded(&self, reason: DiscardReason) {
+		if let Some(observer) = &self.observer {
+			observer.on_discard(reason);
+		}
+		#[cfg(feature = "metrics")]
+		self.metrics.discarded(reason);
+		#[cfg(feature = "tracing")]
+		match reason {
+			DiscardReason::Full => self.tracing.rejected(),
+			DiscardReason::ResetPanicked => self.tracing.reset_panicked(),
+			_ => {},
+		}
+		if let Some(stats) = &self.stats {
+			let counter = match reason {
+				DiscardReason::Full => &stats.rejected,
+				_ => &stats.discards,
+			};
+			counter.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	pub(super) fn detached(&self) {
+		if let Some(stats) = &self.stats {
+			stats.detaches.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	pub(super) fn orphaned(&self) {
+		if let Some(observer) = &self.observer {
+			observer.on_orphan();
+		}
+		#[cfg(feature = "tracing")]
+		self.tracing.orphaned();
+	}
+}
+
+/// The counters behind [`PoolStats`].
+#[derive(Default)]
+pub(super) struct Stats {
+	hits: AtomicUsize,
+	misses: AtomicUsize,
+	returns: AtomicUsize,
+	rejected: AtomicUsize,
+	discards: AtomicUsize,
+	detaches: AtomicUsize,
+	peak_in_use: AtomicUsize,
+}
+
+impl Stats {
+	pub(super) fn snapshot(&self) -> PoolStats {
+		let load = |counter: &AtomicUsize| counter.load(Ordering::Relaxed) as u64;
+		PoolStats {
+			hits: load(&self.hits),
+			misses: load(&self.misses),
+			returns: load(&self.returns),
+			rejected: load(&self.rejected),
+			discards: load(&self.discards),
+			detaches: load(&self.detaches),
+			peak_in_use: self.peak_in_use.load(Ordering::Relaxed),
+		}
+	}
+
+	/// Set every counter to `0`, and the peak to what's in use right now.
+	pub(super) fn reset(&self, in_use: usize) {
+		for counter in [
+			&self.hits,
+			&self.misses,
+			&self.returns,
+			&self.rejected,
+			&self.discards,
+			&self.detaches,
+		] {
+			counter.store(0, Ordering::Relaxed);
+		}
+		self.peak_in_use.store(in_use, Ordering::Relaxed);
+	}
+}
+
+/// A snapshot of a pool's statistics, see [`Pool::stats`](crate::Pool::stats).
+///
+/// Objects that are dropped after their pool aren't counted, since there's no pool left to read
+/// the statistics from. See [`PoolObserver::on_orphan`] to count them.
+///
+/// ```
+/// # use dynamic_pooling::Pool;
+/// let pool = Pool::<String>::builder().capacity(1).track_stats().build().unwrap();
+///
+/// let objects = [pool.take(), pool.take()];
+/// drop(objects);
+/// drop(pool.take());
+///
+/// let stats = pool.stats().unwrap();
+/// assert_eq!(stats.hits, 1);
+/// assert_eq!(stats.misses, 2);
+/// assert_eq!(stats.returns, 2);
+/// assert_eq!(stats.rejected, 1);
+/// assert_eq!(stats.peak_in_use, 2);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct PoolStats {
+	/// Objects that were taken from the available objects.
+	pub hits: u64,
+	/// Objects that were created because none were available.
+	pub misses: u64,
+	/// Objects that were returned and kept by the pool.
+	pub returns: u64,
+	/// Objects that were dropped because the pool was full or over its byte budget when they were
+	/// returned.
+	pub rejected: u64,
+	/// Objects that were dropped for any other reason, like being used up or available for too
+	/// long.
+	pub discards: u64,
+	/// Objects that were [detached](crate::Pooled::detach).
+	pub detaches: u64,
+	/// The most objects that were in use at once.
+	pub peak_in_use: usize,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+	use crate::Pool;
+	use alloc::string::String;
+
+	#[test]
+	fn stats_serialize() {
+		let pool = Pool::<String>::builder()
+			.capacity(1)
+			.track_stats()
+			.build()
+			.unwrap();
+		drop([pool.take(), pool.take()]);
+		drop(pool.take());
+
+		let stats = serde_json::to_value(pool.stats().unwrap()).unwrap();
+		assert_eq!(
+			stats,
+			serde_json::json!({
+				"hits": 1,
+				"misses": 2,
+				"returns": 2,
+				"rejected": 1,
+				"discards": 0,
+				"detaches": 0,
+				"peak_in_use": 2,
+			}),
+		);
+	}
+}
@@ -7,8 +7,51 @@
 
 extern crate alloc;
 
+mod adaptive;
+mod budget;
+mod builder;
+#[cfg(feature = "std")]
+mod cache;
+mod capacity;
+mod clock;
+mod events;
+mod meta;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod object;
 mod pool;
+mod prometheus;
+#[cfg(feature = "std")]
+mod reaper;
 mod reset;
+mod retained;
+#[cfg(feature = "std")]
+mod sharded;
+mod shrink;
+mod sized;
+mod storage;
+#[cfg(feature = "tracing")]
+mod tracing;
+#[cfg(feature = "std")]
+mod wait;
 
-pub use crate::{object::Pooled, pool::Pool, reset::Reset};
+pub use crate::{
+	budget::OverBudget,
+	builder::{BuildError, PoolBuilder},
+	capacity::Capacity,
+	clock::{Clock, ManualClock},
+	events::{DiscardReason, PoolObserver, PoolStats},
+	meta::ObjectMeta,
+	object::Pooled,
+	pool::{Pool, PoolExhausted},
+	prometheus::prometheus_text,
+	reset::Reset,
+	retained::RetainedSize,
+	shrink::{Oversized, Shrink},
+	sized::SizedPool,
+	storage::StorageKind,
+};
+#[cfg(feature = "std")]
+pub use crate::{
+	budget::PoolBudget, clock::StdClock, reaper::Reaper, sharded::ShardedPool, wait::TakeAsync,
+};
@@ -0,0 +1,74 @@
+use core::time::Duration;
+
+/// Information about a pooled object, see [`Pooled::meta`](crate::Pooled::meta).
+///
+/// Times are measured with the pool's [`Clock`](crate::Clock).
+///
+/// ```
+/// # use dynamic_pooling::{Pool, Pooled};
+/// let pool = Pool::<String>::new(69);
+///
+/// let foo = pool.take();
+/// assert!(Pooled::meta(&foo).is_fresh());
+/// drop(foo);
+///
+/// let foo = pool.take();
+/// let meta = Pooled::meta(&foo);
+/// assert!(!meta.is_fresh());
+/// assert_eq!(meta.uses(), 2);
+/// assert!(meta.returned_at().is_some());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectMeta {
+	pub(super) created_at: Duration,
+	pub(super) returned_at: Option<Duration>,
+	pub(super) uses: usize,
+	pub(super) fresh: bool,
+}
+
+impl ObjectMeta {
+	pub(super) fn new(now: Duration) -> Self {
+		Self {
+			created_at: now,
+			returned_at: None,
+			uses: 0,
+			fresh: false,
+		}
+	}
+
+	/// When it became available.
+	pub(super) fn idle_since(&self) -> Duration {
+		self.returned_at.unwrap_or(self.created_at)
+	}
+
+	/// When the object was created.
+	pub fn created_at(&self) -> Duration {
+		self.created_at
+	}
+
+	/// When the object was last returned to the pool, if ever.
+	pub fn returned_at(&self) -> Option<Duration> {
+		self.returned_at
+	}
+
+	/// The number of times the object has been taken, including this time.
+	pub fn uses(&self) -> usize {
+		self.uses
+	}
+
+	/// The number of times the object has been reused.
+	pub fn reuses(&self) -> usize {
+		self.uses.saturating_sub(1)
+	}
+
+	/// Whether the object was created for this use, instead of coming from the pool.
+	pub fn is_fresh(&self) -> bool {
+		self.fresh
+	}
+}
+
+/// An object along with its [`ObjectMeta`].
+pub(super) struct Entry<T> {
+	pub(super) object: T,
+	pub(super) meta: ObjectMeta,
+}
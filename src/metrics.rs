@@ -0,0 +1,156 @@
+use crate::events::DiscardReason;
+use ::metrics::{counter, gauge, Counter, Gauge};
+use alloc::string::String;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The metrics a pool reports with the `metrics` feature.
+///
+/// These are registered when the pool is built, so a recorder must be installed before then.
+///
+/// Pools with the same label share their metrics, so the gauges are only ever moved by how much
+/// this pool's values changed, and the gauges add up every pool's values.
+pub(super) struct Metrics {
+	idle: Gauge,
+	in_use: Gauge,
+	/// The values this pool last added to the gauges.
+	reported_idle: AtomicUsize,
+	reported_in_use: AtomicUsize,
+	hits: Counter,
+	misses: Counter,
+	/// Indexed by [`DiscardReason`].
+	discards: [Counter; DiscardReason::ALL.len()],
+}
+
+impl Metrics {
+	pub(super) fn new(pool: &str) -> Self {
+		let pool = String::from(pool);
+		Self {
+			idle: gauge!("pool_idle", "pool" => pool.clone()),
+			in_use: gauge!("pool_in_use", "pool" => pool.clone()),
+			reported_idle: AtomicUsize::new(0),
+			reported_in_use: AtomicUsize::new(0),
+			hits: counter!("pool_hits_total", "pool" => pool.clone()),
+			misses: counter!("pool_misses_total", "pool" => pool.clone()),
+			discards: DiscardReason::ALL.map(
+				|reason| counter!("pool_discards_total", "pool" => pool.clone(), "reason" => reason.as_str()),
+			),
+		}
+	}
+
+	pub(super) fn taken(&self, fresh: bool) {
+		let counter = if fresh { &self.misses } else { &self.hits };
+		counter.increment(1);
+	}
+
+	pub(super) fn discarded(&self, reason: DiscardReason) {
+		self.discards[reason as usize].increment(1);
+	}
+
+	/// Update the gauges.
+	pub(super) fn observe(&self, idle: usize, in_use: usize) {
+		report(&self.idle, &self.reported_idle, idle);
+		report(&self.in_use, &self.reported_in_use, in_use);
+	}
+}
+
+impl Drop for Metrics {
+	fn drop(&mut self) {
+		self.observe(0, 0);
+	}
+}
+
+/// Move a gauge by how much a value changed since it was last reported.
+fn report(gauge: &Gauge, reported: &AtomicUsize, value: usize) {
+	let old = reported.swap(value, Ordering::Relaxed);
+	if value > old {
+		gauge.increment((value - old) as f64);
+	} else if value < old {
+		gauge.decrement((old - value) as f64);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Pool;
+	use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+	/// Record metrics from `f`, returning its result and a function that gets the value of a
+	/// metric by its name and labels.
+	#[allow(clippy::type_complexity)]
+	fn record<R>(f: impl FnOnce() -> R) -> (R, impl Fn(&str, &[(&str, &str)]) -> DebugValue) {
+		let recorder = DebuggingRecorder::new();
+		let snapshotter = recorder.snapshotter();
+		let result = metrics::with_local_recorder(&recorder, f);
+		// counters are reset whenever a snapshot is taken, so only take one
+		let snapshot = snapshotter.snapshot().into_vec();
+		let metric = move |name: &str, labels: &[(&str, &str)]| {
+			let (.., value) = snapshot
+				.iter()
+				.find(|(key, ..)| {
+					let key = key.key();
+					let key_labels = key.labels().map(|label| (label.key(), label.value()));
+					key.name() == name && key_labels.eq(labels.iter().copied())
+				})
+				.unwrap_or_else(|| panic!("missing metric {name} {labels:?}"));
+			match value {
+				DebugValue::Counter(value) => DebugValue::Counter(*value),
+				DebugValue::Gauge(value) => DebugValue::Gauge(*value),
+				DebugValue::Histogram(values) => DebugValue::Histogram(values.clone()),
+			}
+		};
+		(result, metric)
+	}
+
+	#[test]
+	fn reports_labelled_metrics() {
+		let (_pool, metric) = record(|| {
+			let pool = Pool::<String>::builder()
+				.capacity(1)
+				.name("strings")
+				.build()
+				.unwrap();
+			drop([pool.take(), pool.take()]);
+			drop(pool.take());
+			pool
+		});
+
+		let pool = [("pool", "strings")];
+		assert_eq!(metric("pool_hits_total", &pool), DebugValue::Counter(1));
+		assert_eq!(metric("pool_misses_total", &pool), DebugValue::Counter(2));
+		let full = [("pool", "strings"), ("reason", "full")];
+		assert_eq!(metric("pool_discards_total", &full), DebugValue::Counter(1));
+		let idle = [("pool", "strings"), ("reason", "idle")];
+		assert_eq!(metric("pool_discards_total", &idle), DebugValue::Counter(0));
+		assert_eq!(metric("pool_idle", &pool), DebugValue::Gauge(1.0.into()));
+		assert_eq!(metric("pool_in_use", &pool), DebugValue::Gauge(0.0.into()));
+	}
+
+	#[test]
+	fn unnamed_pools_are_labelled_with_their_type() {
+		let ((), metric) = record(|| {
+			let pool = Pool::<Vec<u8>>::new(69);
+			drop(pool.take());
+		});
+
+		let pool = [("pool", "alloc::vec::Vec<u8>")];
+		assert_eq!(metric("pool_misses_total", &pool), DebugValue::Counter(1));
+	}
+
+	#[test]
+	fn pools_with_the_same_label_add_up() {
+		let (_pools, metric) = record(|| {
+			let [first, second] = [(); 2].map(|()| Pool::<Vec<u8>>::new(69));
+			let object = first.take();
+			drop(second.take());
+			let third = Pool::<Vec<u8>>::new(69);
+			drop(third.take());
+			drop(third);
+			(first, second, object)
+		});
+
+		let pool = [("pool", "alloc::vec::Vec<u8>")];
+		assert_eq!(metric("pool_misses_total", &pool), DebugValue::Counter(3));
+		assert_eq!(metric("pool_idle", &pool), DebugValue::Gauge(1.0.into()));
+		assert_eq!(metric("pool_in_use", &pool), DebugValue::Gauge(1.0.into()));
+	}
+}
@@ -1,4 +1,6 @@
 use crate::{
+	events::Events,
+	meta::{Entry, ObjectMeta},
 	pool::{Pool, PoolInner},
 	reset::Reset,
 };
@@ -14,7 +16,7 @@ use core::{
 /// An object taken from a [`Pool`].
 ///
 /// When dropped, it will be [`Reset`] and returned to the pool if it has spare capacity.
-pub struct Pooled<T: Default + Reset> {
+pub struct Pooled<T: Reset> {
 	pool_inner: Weak<PoolInner<T>>,
 
 	// (internal docs, users don't need to worry about this)
@@ -23,13 +25,18 @@ pub struct Pooled<T: Default + Reset> {
 	/// Almost all methods (including dereferencing) assume this is [`Some`], and they'll panic
 	/// otherwise.
 	object: Option<T>,
+	meta: ObjectMeta,
+	/// Used to report being dropped after the pool.
+	events: Option<Arc<Events>>,
 }
 
-impl<T: Default + Reset> Pooled<T> {
-	pub(super) fn new(object: T, pool: &Pool<T>) -> Self {
+impl<T: Reset> Pooled<T> {
+	pub(super) fn new(entry: Entry<T>, pool: &Pool<T>) -> Self {
 		Self {
-			object: Some(object),
+			object: Some(entry.object),
+			meta: entry.meta,
 			pool_inner: Arc::downgrade(&pool.inner),
+			events: pool.inner.events.clone(),
 		}
 	}
 
@@ -47,9 +54,20 @@ impl<T: Default + Reset> Pooled<T> {
 	/// assert_eq!(pool.in_use(), 0);
 	/// ```
 	pub fn detach(mut this: Self) -> T {
+		if let Some(pool_inner) = this.pool_inner.upgrade() {
+			pool_inner.forget();
+			pool_inner.record(Events::detached);
+		}
 		this.object.take().expect("always some")
 	}
 
+	/// Get information about this object, like how many times it's been used.
+	///
+	/// See [`ObjectMeta`] for an example.
+	pub fn meta(this: &Self) -> &ObjectMeta {
+		&this.meta
+	}
+
 	/// Get the pool associated with this object.
 	///
 	/// Objects can outlive the pool they came from, so this returns an [`Option`].
@@ -67,55 +85,64 @@ impl<T: Default + Reset> Pooled<T> {
 	}
 }
 
-impl<T: Default + Reset> Drop for Pooled<T> {
+impl<T: Reset> Drop for Pooled<T> {
 	fn drop(&mut self) {
-		if let Some(pool_inner) = self.pool_inner.upgrade() {
-			if let Some(mut object) = self.object.take() {
-				object.reset();
-				let _ = pool_inner.push(object);
-			}
+		let Some(object) = self.object.take() else {
+			// detached
+			return;
+		};
+		match self.pool_inner.upgrade() {
+			Some(pool_inner) => pool_inner.recycle(Entry {
+				object,
+				meta: self.meta,
+			}),
+			None => {
+				if let Some(events) = &self.events {
+					events.orphaned();
+				}
+			},
 		}
 	}
 }
 
-impl<T: Default + Reset> Deref for Pooled<T> {
+impl<T: Reset> Deref for Pooled<T> {
 	type Target = T;
 	fn deref(&self) -> &Self::Target {
 		self.object.as_ref().expect("always some")
 	}
 }
 
-impl<T: Default + Reset> DerefMut for Pooled<T> {
+impl<T: Reset> DerefMut for Pooled<T> {
 	fn deref_mut(&mut self) -> &mut Self::Target {
 		self.object.as_mut().expect("always some")
 	}
 }
 
-impl<T: Default + Reset> AsRef<T> for Pooled<T> {
+impl<T: Reset> AsRef<T> for Pooled<T> {
 	fn as_ref(&self) -> &T {
 		self
 	}
 }
 
-impl<T: Default + Reset> AsMut<T> for Pooled<T> {
+impl<T: Reset> AsMut<T> for Pooled<T> {
 	fn as_mut(&mut self) -> &mut T {
 		self
 	}
 }
 
-impl<T: Default + Reset> Borrow<T> for Pooled<T> {
+impl<T: Reset> Borrow<T> for Pooled<T> {
 	fn borrow(&self) -> &T {
 		self
 	}
 }
 
-impl<T: Default + Reset> BorrowMut<T> for Pooled<T> {
+impl<T: Reset> BorrowMut<T> for Pooled<T> {
 	fn borrow_mut(&mut self) -> &mut T {
 		self
 	}
 }
 
-impl<T: Default + Reset> Hash for Pooled<T>
+impl<T: Reset> Hash for Pooled<T>
 where
 	T: Hash,
 {
@@ -124,7 +151,7 @@ where
 	}
 }
 
-impl<T: Default + Reset> PartialEq for Pooled<T>
+impl<T: Reset> PartialEq for Pooled<T>
 where
 	T: PartialEq<T>,
 {
@@ -133,7 +160,7 @@ where
 	}
 }
 
-impl<T: Default + Reset> PartialEq<T> for Pooled<T>
+impl<T: Reset> PartialEq<T> for Pooled<T>
 where
 	T: PartialEq<T>,
 {
@@ -142,9 +169,9 @@ where
 	}
 }
 
-impl<T: Default + Reset> Eq for Pooled<T> where T: Eq {}
+impl<T: Reset> Eq for Pooled<T> where T: Eq {}
 
-impl<T: Default + Reset> PartialOrd for Pooled<T>
+impl<T: Reset> PartialOrd for Pooled<T>
 where
 	T: PartialOrd<T>,
 {
@@ -153,7 +180,7 @@ where
 	}
 }
 
-impl<T: Default + Reset> Ord for Pooled<T>
+impl<T: Reset> Ord for Pooled<T>
 where
 	T: Ord,
 {
@@ -162,13 +189,13 @@ where
 	}
 }
 
-impl<T: Default + Reset + PartialOrd<T>> PartialOrd<T> for Pooled<T> {
+impl<T: Reset + PartialOrd<T>> PartialOrd<T> for Pooled<T> {
 	fn partial_cmp(&self, other: &T) -> Option<Ordering> {
 		(**self).partial_cmp(other)
 	}
 }
 
-impl<T: Default + Reset> Debug for Pooled<T>
+impl<T: Reset> Debug for Pooled<T>
 where
 	T: Debug,
 {
@@ -177,7 +204,7 @@ where
 	}
 }
 
-impl<T: Default + Reset> Display for Pooled<T>
+impl<T: Reset> Display for Pooled<T>
 where
 	T: Display,
 {
@@ -1,28 +1,616 @@
-use crate::{Pooled, Reset};
-use alloc::sync::Arc;
-use crossbeam_queue::ArrayQueue;
+use crate::{
+	adaptive::Adaptive,
+	budget::Retained,
+	events::{DiscardReason, Events},
+	meta::{Entry, ObjectMeta},
+	shrink::RetainLimit,
+	sized::Classes,
+	storage::{AnyStorage, Storage},
+	Clock, PoolBuilder, PoolStats, Pooled, Reset,
+};
+#[cfg(feature = "std")]
+use crate::{cache::ThreadCache, clock::StdClock, wait::Waiters};
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use core::{
+	any::type_name,
+	fmt::{self, Debug, Display},
+	mem,
+	sync::atomic::{AtomicUsize, Ordering},
+	time::Duration,
+};
 
 /// A lock-free, thread-safe object pool.
-pub struct Pool<T: Default + Reset> {
+pub struct Pool<T: Reset> {
 	/// A [`Pool`] is just a wrapper over this.
 	pub(super) inner: Arc<PoolInner<T>>,
 }
 
-// todo: don't show ArrayQueue to the rest of the crate
-pub(super) type PoolInner<T> = ArrayQueue<T>;
+/// The state shared between a [`Pool`] and its [`Pooled`] objects.
+pub(super) struct PoolInner<T> {
+	/// Where available objects are kept.
+	pub(super) storage: AnyStorage<T>,
+	/// Takes priority over the storage if set.
+	#[cfg(feature = "std")]
+	cache: Option<Box<dyn ThreadCache<T>>>,
+	/// The number of available objects, which is counted separately from the storage so that the
+	/// capacity can change.
+	len: AtomicUsize,
+	capacity: AtomicUsize,
+	adaptive: Option<Adaptive>,
+	/// Used to create new objects when the queue is empty.
+	factory: Factory<T>,
+	/// Overrides [`Reset::reset`] if set.
+	reset: Option<ResetFn<T>>,
+	/// Applied to objects after they're reset.
+	retain_limit: Option<RetainLimit<T>>,
+	/// Counts the bytes retained by available objects if set.
+	pub(super) retained: Option<Retained<T>>,
+	/// Used to pick which object to keep when the pool is full, see
+	/// [`PoolBuilder::prefer_larger`].
+	admission: Option<Measure<T>>,
+	/// Overrides the default clock if set.
+	clock: Option<Box<dyn Clock>>,
+	max_uses: Option<usize>,
+	max_lifetime: Option<Duration>,
+	/// Available objects are dropped when they're popped after this long.
+	pub(super) idle_timeout: Option<Duration>,
+	/// The number of available objects a reaper keeps.
+	pub(super) min_idle: usize,
+	/// The number of objects that belong to the pool, both available and in use.
+	live: AtomicUsize,
+	max_live: Option<usize>,
+	name: Option<String>,
+	/// Shared with objects if set, so that they can report being dropped after the pool.
+	pub(super) events: Option<Arc<Events>>,
+	#[cfg(feature = "std")]
+	pub(super) waiters: Waiters,
+}
+
+/// A custom reset function, see [`PoolBuilder::reset`].
+pub(super) type ResetFn<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+
+/// How a [`Pool`] creates new objects.
+pub(super) enum Factory<T> {
+	/// A plain function pointer, so that [`Pool::new`] doesn't need `T: 'static`.
+	Fn(fn() -> T),
+	Boxed(Box<dyn Fn() -> T + Send + Sync>),
+}
+
+impl<T> Factory<T> {
+	fn create(&self) -> T {
+		match self {
+			Self::Fn(factory) => factory(),
+			Self::Boxed(factory) => factory(),
+		}
+	}
+}
+
+/// How a [`Pool`] measures the size of objects.
+pub(super) enum Measure<T> {
+	/// A plain function pointer, so that trait implementations don't need `T: 'static`.
+	Fn(fn(&T) -> usize),
+	Boxed(Box<dyn Fn(&T) -> usize + Send + Sync>),
+}
+
+impl<T> Measure<T> {
+	fn measure(&self, object: &T) -> usize {
+		match self {
+			Self::Fn(measure) => measure(object),
+			Self::Boxed(measure) => measure(object),
+		}
+	}
+}
+
+impl<T: Reset> PoolInner<T> {
+	pub(super) fn new(builder: PoolBuilder<T>) -> Self {
+		let adaptive = builder
+			.adaptive
+			.map(|(bounds, window)| Adaptive::new(bounds, window));
+		let inner = Self {
+			storage: match builder.size_classes {
+				Some(capacity_of) => AnyStorage::Classes(Classes::new(capacity_of)),
+				// the storage spills over if the capacity grows
+				None => AnyStorage::new(builder.storage, builder.capacity),
+			},
+			#[cfg(feature = "std")]
+			cache: builder.thread_cache,
+			len: AtomicUsize::new(0),
+			capacity: AtomicUsize::new(builder.capacity),
+			adaptive,
+			factory: builder.factory.expect("validated by the builder"),
+			reset: builder.reset,
+			retain_limit: builder.retain_limit,
+			retained: builder.retained,
+			admission: builder.admission,
+			clock: builder.clock,
+			max_uses: builder.max_uses,
+			max_lifetime: builder.max_lifetime,
+			idle_timeout: builder.idle_timeout,
+			min_idle: builder.min_idle,
+			live: AtomicUsize::new(builder.prewarm),
+			max_live: builder.max_live,
+			events: Events::new(
+				builder.track_stats,
+				builder.observer,
+				builder.name.as_deref(),
+				type_name::<T>(),
+			),
+			name: builder.name,
+			#[cfg(feature = "std")]
+			waiters: Waiters::new(),
+		};
+		for _ in 0..builder.prewarm {
+			let object = inner.factory.create();
+			inner.record(Events::created);
+			if inner.reserve_for(&object) {
+				inner.storage.push(Entry {
+					object,
+					meta: ObjectMeta::new(inner.now()),
+				});
+			} else {
+				inner.discard(DiscardReason::Full);
+			}
+		}
+		inner
+	}
+
+	/// Reset an object and return it to the queue, dropping it if the queue is full or the object
+	/// is used up.
+	pub(super) fn recycle(self: &Arc<Self>, mut entry: Entry<T>) {
+		let now = self.now();
+		if let Some(reason) = self.is_used_up(&entry.meta, now) {
+			drop(entry);
+			return self.discard(reason);
+		}
+		let guard = ResetGuard { pool: self };
+		self.reset(&mut entry.object);
+		mem::forget(guard);
+		if let Some(retain_limit) = &self.retain_limit {
+			if !retain_limit.apply(&mut entry.object) {
+				drop(entry);
+				return self.discard(DiscardReason::Oversized);
+			}
+		}
+		entry.meta.returned_at = Some(now);
+		entry.meta.fresh = false;
+		match (self.push(entry), &self.admission) {
+			(Ok(()), _) => {
+				self.record(Events::returned);
+				self.notify();
+			},
+			(Err(entry), Some(measure)) => self.admit(entry, measure),
+			(Err(entry), None) => {
+				drop(entry);
+				self.discard(DiscardReason::Full);
+			},
+		}
+	}
+
+	fn reset(&self, object: &mut T) {
+		match &self.reset {
+			Some(reset) => reset(object),
+			None => object.reset(),
+		}
+	}
+}
+
+/// Discards an object if resetting it panics, since it's dropped while unwinding.
+struct ResetGuard<'a, T> {
+	pool: &'a PoolInner<T>,
+}
+
+impl<T> Drop for ResetGuard<'_, T> {
+	fn drop(&mut self) {
+		self.pool.discard(DiscardReason::ResetPanicked);
+	}
+}
+
+/// How many available objects are looked at to make room for a returned object, see
+/// [`PoolBuilder::prefer_larger_by`].
+const ADMISSION_CANDIDATES: usize = 4;
+
+/// Available objects that were popped to be measured, which are put back when this is dropped,
+/// even if measuring them panics.
+struct Candidates<'a, T> {
+	pool: &'a PoolInner<T>,
+	objects: Vec<Entry<T>>,
+}
+
+impl<T> Drop for Candidates<'_, T> {
+	fn drop(&mut self) {
+		for candidate in self.objects.drain(..) {
+			// these are still counted as available
+			self.pool.storage.push(candidate);
+			self.pool.notify();
+		}
+	}
+}
+
+impl<T> PoolInner<T> {
+	/// Take an available object.
+	pub(super) fn pop(self: &Arc<Self>) -> Option<Entry<T>> {
+		self.pop_idle().map(|entry| self.hand_out(entry))
+	}
+
+	/// Pop an object, or create one if that wouldn't exceed the maximum number of live objects.
+	fn pop_or_create(self: &Arc<Self>) -> Option<Entry<T>> {
+		self.pop_or_create_with(|| self.pop_idle(), || self.factory.create())
+	}
+
+	/// Like [`PoolInner::pop_or_create`], but with a custom way to pop and create objects.
+	pub(super) fn pop_or_create_with(
+		&self,
+		pop: impl Fn() -> Option<Entry<T>>,
+		create: impl FnOnce() -> T,
+	) -> Option<Entry<T>> {
+		let entry = pop()
+			.or_else(|| {
+				self.try_add_live().then(|| Entry {
+					object: create(),
+					meta: ObjectMeta {
+						fresh: true,
+						..ObjectMeta::new(self.now())
+					},
+				})
+			})
+			// another object may have been returned in the meantime
+			.or_else(&pop)?;
+		Some(self.hand_out(entry))
+	}
+
+	/// Record that an object was taken.
+	pub(super) fn hand_out(&self, mut entry: Entry<T>) -> Entry<T> {
+		entry.meta.uses += 1;
+		self.record(|events| events.taken(entry.meta.fresh, self.in_use()));
+		self.observe_take();
+		entry
+	}
+
+	/// Whether an object has reached its maximum uses or lifetime, and which one.
+	fn is_used_up(&self, meta: &ObjectMeta, now: Duration) -> Option<DiscardReason> {
+		if self.max_uses.is_some_and(|max_uses| meta.uses >= max_uses) {
+			Some(DiscardReason::MaxUses)
+		} else if self
+			.max_lifetime
+			.is_some_and(|max_lifetime| now.saturating_sub(meta.created_at) >= max_lifetime)
+		{
+			Some(DiscardReason::MaxLifetime)
+		} else {
+			None
+		}
+	}
+
+	/// Take an available object, checking this thread's cache first.
+	fn pop_idle(self: &Arc<Self>) -> Option<Entry<T>> {
+		#[cfg(feature = "std")]
+		if let Some(cache) = &self.cache {
+			let object = self.unexpired(|| {
+				let idle = cache.pop(self)?;
+				self.unreserve(&idle.object);
+				Some(idle)
+			});
+			if object.is_some() {
+				return object;
+			}
+		}
+		self.pop_stored_with(Storage::pop)
+	}
+
+	/// Take an available object from the storage with `pop`, skipping thread caches.
+	pub(super) fn pop_stored_with(
+		&self,
+		pop: impl Fn(&AnyStorage<T>) -> Option<Entry<T>>,
+	) -> Option<Entry<T>> {
+		self.unexpired(|| self.pop_entry_with(&pop))
+	}
+
+	/// Like [`PoolInner::pop_stored_with`], but returns objects even if they've expired.
+	fn pop_entry_with(&self, pop: impl Fn(&AnyStorage<T>) -> Option<Entry<T>>) -> Option<Entry<T>> {
+		let idle = pop(&self.storage)?;
+		self.unreserve(&idle.object);
+		Some(idle)
+	}
+
+	/// Pop objects until one hasn't been available for longer than the idle timeout, dropping the
+	/// rest.
+	fn unexpired(&self, mut pop: impl FnMut() -> Option<Entry<T>>) -> Option<Entry<T>> {
+		let Some(idle_timeout) = self.idle_timeout else {
+			return pop();
+		};
+		let now = self.now();
+		loop {
+			let entry = pop()?;
+			if now.saturating_sub(entry.meta.idle_since()) < idle_timeout {
+				return Some(entry);
+			}
+			drop(entry);
+			self.discard(DiscardReason::Idle);
+		}
+	}
+
+	/// Drop available objects in the storage that have been available for at least `older_than`,
+	/// keeping at least `min_idle` available objects. Returns how many were dropped.
+	pub(super) fn evict_idle(&self, older_than: Duration, min_idle: usize) -> usize {
+		let now = self.now();
+		let mut removable = self.len().saturating_sub(min_idle);
+		let expired = self.storage.retain(|idle| {
+			if removable == 0 || now.saturating_sub(idle.meta.idle_since()) < older_than {
+				return true;
+			}
+			removable -= 1;
+			false
+		});
+		let count = expired.len();
+		for idle in expired {
+			self.unreserve(&idle.object);
+			drop(idle);
+			self.discard(DiscardReason::Idle);
+		}
+		count
+	}
+
+	/// Returns the object if the pool is full.
+	fn push(self: &Arc<Self>, idle: Entry<T>) -> Result<(), Entry<T>> {
+		if !self.reserve_for(&idle.object) {
+			return Err(idle);
+		}
+		#[cfg(feature = "std")]
+		let idle = match &self.cache {
+			Some(cache) => match cache.push(self, idle) {
+				Ok(()) => return Ok(()),
+				Err(idle) => idle,
+			},
+			None => idle,
+		};
+		self.storage.push(idle);
+		Ok(())
+	}
+
+	/// Make room for an object that was returned to a full pool by evicting the smallest of a few
+	/// available objects in the storage, unless that one is at least as large.
+	fn admit(self: &Arc<Self>, idle: Entry<T>, measure: &Measure<T>) {
+		let size = measure.measure(&idle.object);
+		// only a few objects are looked at, so that other threads don't find the storage empty for
+		// long, and they're measured outside of any lock in case that panics
+		let mut candidates = Candidates {
+			pool: self,
+			objects: Vec::with_capacity(ADMISSION_CANDIDATES),
+		};
+		let mut smallest = None;
+		for index in 0..ADMISSION_CANDIDATES {
+			let Some(candidate) = self.storage.pop() else {
+				break;
+			};
+			candidates.objects.push(candidate);
+			let candidate_size = measure.measure(&candidates.objects[index].object);
+			if candidate_size < smallest.map_or(size, |(_, size)| size) {
+				smallest = Some((index, candidate_size));
+			}
+		}
+		let Some((index, _)) = smallest else {
+			drop(idle);
+			return self.discard(DiscardReason::Full);
+		};
+		// the evicted object's slot goes straight to the returned object, so that no other
+		// object can take it in the meantime
+		if !self.swap_retained(&candidates.objects[index].object, &idle.object) {
+			drop(idle);
+			return self.discard(DiscardReason::Full);
+		}
+		let evicted = candidates.objects.remove(index);
+		drop(candidates);
+		self.storage.push(idle);
+		drop(evicted);
+		self.discard(DiscardReason::Evicted);
+		self.record(Events::returned);
+		self.notify();
+	}
+
+	/// Count `new` instead of `old` towards the byte budget, returning `false` if it doesn't fit.
+	fn swap_retained(&self, old: &T, new: &T) -> bool {
+		let Some(retained) = &self.retained else {
+			return true;
+		};
+		let (old, new) = (retained.measure(old), retained.measure(new));
+		if new > old {
+			retained.try_add(new - old, || self.evict_one())
+		} else {
+			retained.remove(old - new);
+			true
+		}
+	}
+
+	/// Make room for an object, returning `false` if the pool is full or over its byte budget.
+	fn reserve_for(&self, object: &T) -> bool {
+		if !self.reserve() {
+			return false;
+		}
+		let fits = self
+			.retained
+			.as_ref()
+			.is_none_or(|retained| retained.try_add(retained.measure(object), || self.evict_one()));
+		if !fits {
+			self.len.fetch_sub(1, Ordering::Relaxed);
+		}
+		fits
+	}
+
+	/// Drop an available object from the storage, returning `false` if there weren't any.
+	pub(super) fn evict_one(&self) -> bool {
+		match self.pop_entry_with(Storage::pop) {
+			Some(object) => {
+				drop(object);
+				self.discard(DiscardReason::Evicted);
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Stop counting an object that was taken from the available objects.
+	fn unreserve(&self, object: &T) {
+		self.len.fetch_sub(1, Ordering::Relaxed);
+		if let Some(retained) = &self.retained {
+			retained.remove(retained.measure(object));
+		}
+	}
+
+	/// Make room for another available object, returning `false` if the pool is full.
+	fn reserve(&self) -> bool {
+		let capacity = self.capacity.load(Ordering::Relaxed);
+		self.len
+			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |len| {
+				(len < capacity).then_some(len + 1)
+			})
+			.is_ok()
+	}
+
+	/// The current time according to the pool's clock.
+	pub(super) fn now(&self) -> Duration {
+		match &self.clock {
+			Some(clock) => clock.now(),
+			#[cfg(feature = "std")]
+			None => StdClock.now(),
+			#[cfg(not(feature = "std"))]
+			None => Duration::ZERO,
+		}
+	}
+
+	pub(super) fn len(&self) -> usize {
+		self.len.load(Ordering::Relaxed)
+	}
+
+	pub(super) fn capacity(&self) -> usize {
+		self.capacity.load(Ordering::Relaxed)
+	}
+
+	pub(super) fn in_use(&self) -> usize {
+		self.live.load(Ordering::Relaxed).saturating_sub(self.len())
+	}
+
+	/// Let the adaptive capacity know that an object was taken.
+	fn observe_take(&self) {
+		if let Some(adaptive) = &self.adaptive {
+			if let Some(capacity) = adaptive.observe(self.in_use()) {
+				self.set_capacity(capacity);
+			}
+		}
+	}
+
+	/// Change the capacity, dropping available objects that no longer fit.
+	pub(super) fn set_capacity(&self, capacity: usize) {
+		self.capacity.store(capacity, Ordering::Relaxed);
+		self.shrink_to(capacity);
+	}
+
+	/// Drop available objects until there are at most `len` of them.
+	pub(super) fn shrink_to(&self, len: usize) {
+		while self.len() > len && self.evict_one() {}
+	}
+
+	/// Count a new live object, returning `false` if there are too many.
+	fn try_add_live(&self) -> bool {
+		match self.max_live {
+			None => {
+				self.live.fetch_add(1, Ordering::Relaxed);
+				true
+			},
+			Some(max_live) => self
+				.live
+				.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |live| {
+					(live < max_live).then_some(live + 1)
+				})
+				.is_ok(),
+		}
+	}
+
+	/// Record an event if anything is keeping track.
+	pub(super) fn record(&self, event: impl FnOnce(&Events)) {
+		if let Some(events) = &self.events {
+			event(events);
+			#[cfg(feature = "metrics")]
+			events.metrics.observe(self.len(), self.in_use());
+		}
+	}
+
+	/// Stop counting an object that was dropped instead of being kept.
+	fn discard(&self, reason: DiscardReason) {
+		self.forget();
+		self.record(|events| events.discarded(reason));
+	}
+
+	/// Stop counting an object that no longer belongs to the pool.
+	pub(super) fn forget(&self) {
+		self.live.fetch_sub(1, Ordering::Relaxed);
+		// there's room for a new object now
+		self.notify();
+	}
+
+	/// Let a waiter know that it might be able to take an object.
+	fn notify(&self) {
+		#[cfg(feature = "std")]
+		self.waiters.notify_one();
+	}
+}
 
 impl<T: Default + Reset> Pool<T> {
 	/// Create a new pool with the specified capacity.
 	///
-	/// Note: The capacity will be fully allocated.
+	/// New objects are created with [`Default`]. See [`Pool::with_factory`] to use something else.
+	///
+	/// Note: The capacity will be fully allocated. See [`StorageKind`](crate::StorageKind) to avoid this.
 	///
 	/// # Panics
 	/// Panics if the capacity is `0`.
 	pub fn new(capacity: usize) -> Self {
-		assert!(capacity > 0, "capacity must be more than 0");
-		Self {
-			inner: Arc::new(PoolInner::new(capacity)),
-		}
+		Self::builder()
+			.capacity(capacity)
+			.build()
+			.unwrap_or_else(|error| panic!("{error}"))
+	}
+}
+
+impl<T: Reset> Pool<T> {
+	/// Create a new pool with the specified capacity, using `factory` to create new objects.
+	///
+	/// Note: The capacity will be fully allocated. See [`StorageKind`](crate::StorageKind) to avoid this.
+	///
+	/// ```
+	/// # use dynamic_pooling::{Pool, Reset};
+	/// # use std::sync::Arc;
+	/// struct Parser {
+	/// 	config: Arc<str>,
+	/// 	buffer: Vec<u8>,
+	/// }
+	///
+	/// impl Reset for Parser {
+	/// 	fn reset(&mut self) {
+	/// 		self.buffer.clear();
+	/// 	}
+	/// }
+	///
+	/// let config: Arc<str> = Arc::from("strict");
+	/// let pool = Pool::with_factory(69, move || Parser {
+	/// 	config: Arc::clone(&config),
+	/// 	buffer: Vec::with_capacity(1024),
+	/// });
+	///
+	/// let parser = pool.take();
+	/// assert_eq!(&*parser.config, "strict");
+	/// assert!(parser.buffer.capacity() >= 1024);
+	/// ```
+	///
+	/// # Panics
+	/// Panics if the capacity is `0`.
+	pub fn with_factory<F>(capacity: usize, factory: F) -> Self
+	where
+		F: Fn() -> T + Send + Sync + 'static,
+	{
+		PoolBuilder::new()
+			.capacity(capacity)
+			.factory(factory)
+			.build()
+			.unwrap_or_else(|error| panic!("{error}"))
 	}
 
 	/// Take an object from the pool, creating a new one if none are available.
@@ -33,8 +621,36 @@ impl<T: Default + Reset> Pool<T> {
 	/// let mut string: Pooled<String> = pool.take();
 	/// // do something with it...
 	/// ```
+	///
+	/// # Panics
+	/// Panics if the pool has reached its [maximum number of live objects](Pool::max_live). See
+	/// [`Pool::try_take_bounded`] to handle this instead.
 	pub fn take(&self) -> Pooled<T> {
-		Pooled::new(self.inner.pop().unwrap_or_default(), self)
+		self.try_take_bounded()
+			.unwrap_or_else(|error| panic!("{error}"))
+	}
+
+	/// Take an object from the pool, creating a new one if none are available and the pool hasn't
+	/// reached its [maximum number of live objects](Pool::max_live).
+	///
+	/// This never fails if the pool has no maximum.
+	///
+	/// ```
+	/// # use dynamic_pooling::{Pool, PoolExhausted};
+	/// let pool = Pool::<String>::builder().capacity(69).max_live(2).build().unwrap();
+	/// let foo = pool.try_take_bounded().unwrap();
+	/// let bar = pool.try_take_bounded().unwrap();
+	/// assert_eq!(pool.try_take_bounded().err(), Some(PoolExhausted));
+	///
+	/// // return an object
+	/// drop(foo);
+	/// assert!(pool.try_take_bounded().is_ok());
+	/// ```
+	pub fn try_take_bounded(&self) -> Result<Pooled<T>, PoolExhausted> {
+		self.inner
+			.pop_or_create()
+			.map(|entry| Pooled::new(entry, self))
+			.ok_or(PoolExhausted)
 	}
 
 	/// Take an object from the pool, returning [`None`] if none are available.
@@ -54,7 +670,7 @@ impl<T: Default + Reset> Pool<T> {
 	/// assert!(pool.try_take().is_some());
 	/// ```
 	pub fn try_take(&self) -> Option<Pooled<T>> {
-		self.inner.pop().map(|object| Pooled::new(object, self))
+		self.inner.pop().map(|entry| Pooled::new(entry, self))
 	}
 
 	/// The number of available objects in the pool.
@@ -96,7 +712,25 @@ impl<T: Default + Reset> Pool<T> {
 	/// assert_eq!(pool.in_use(), 0);
 	/// ```
 	pub fn in_use(&self) -> usize {
-		Arc::weak_count(&self.inner)
+		self.inner.in_use()
+	}
+
+	/// The number of objects that belong to the pool, both available and in use.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool as HiddenPool;
+	/// # type Pool = HiddenPool<String>;
+	/// let pool = Pool::new(69);
+	///
+	/// // use 2 objects, then return 1
+	/// let foo = pool.take();
+	/// let bar = pool.take();
+	/// drop(foo);
+	///
+	/// assert_eq!(pool.live(), 2);
+	/// ```
+	pub fn live(&self) -> usize {
+		self.inner.live.load(Ordering::Relaxed)
 	}
 
 	/// Whether the pool is empty.
@@ -117,7 +751,7 @@ impl<T: Default + Reset> Pool<T> {
 	/// assert!(pool.is_empty());
 	/// ```
 	pub fn is_empty(&self) -> bool {
-		self.inner.is_empty()
+		self.len() == 0
 	}
 
 	/// Whether the pool is full.
@@ -138,11 +772,14 @@ impl<T: Default + Reset> Pool<T> {
 	/// assert_eq!(pool.is_full(), false);
 	/// ```
 	pub fn is_full(&self) -> bool {
-		self.inner.is_full()
+		self.len() >= self.capacity()
 	}
 
 	/// The maximum capacity of the pool.
 	///
+	/// This can be changed with [`Pool::set_capacity`], and changes over time if the pool is
+	/// [adaptive](crate::PoolBuilder::adaptive).
+	///
 	/// ```
 	/// # use dynamic_pooling::Pool as HiddenPool;
 	/// # type Pool = HiddenPool<String>;
@@ -155,20 +792,222 @@ impl<T: Default + Reset> Pool<T> {
 
 	/// The spare capacity of the pool.
 	pub fn spare_capacity(&self) -> usize {
-		self.capacity() - self.len()
+		self.capacity().saturating_sub(self.len())
+	}
+
+	/// Change the capacity of the pool, dropping available objects that no longer fit.
+	///
+	/// Objects that are in use will still be returned to this pool. If the pool is
+	/// [adaptive](crate::PoolBuilder::adaptive), the capacity is clamped to its bounds and will
+	/// keep adjusting within them.
+	///
+	/// Note: At most the capacity the pool was built with is allocated up front, depending on its
+	/// [`StorageKind`](crate::StorageKind).
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool as HiddenPool;
+	/// # type Pool = HiddenPool<String>;
+	/// let pool = Pool::new(2);
+	/// let objects = [pool.take(), pool.take(), pool.take()];
+	///
+	/// pool.set_capacity(3);
+	/// drop(objects);
+	/// assert_eq!(pool.len(), 3);
+	///
+	/// pool.set_capacity(1);
+	/// assert_eq!(pool.len(), 1);
+	///
+	/// let adaptive = HiddenPool::<String>::builder().adaptive(1..=4, 8).build().unwrap();
+	/// adaptive.set_capacity(100);
+	/// assert_eq!(adaptive.capacity(), 4);
+	/// ```
+	///
+	/// # Panics
+	/// Panics if the capacity is `0`.
+	pub fn set_capacity(&self, capacity: usize) {
+		assert!(capacity > 0, "capacity must be more than 0");
+		let capacity = match &self.inner.adaptive {
+			Some(adaptive) => capacity.clamp(adaptive.min, adaptive.max),
+			None => capacity,
+		};
+		self.inner.set_capacity(capacity);
+	}
+
+	/// Drop available objects until there are at most `len` of them, without changing the capacity.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool as HiddenPool;
+	/// # type Pool = HiddenPool<String>;
+	/// let pool = Pool::new(69);
+	/// let objects = [pool.take(), pool.take(), pool.take()];
+	/// drop(objects);
+	///
+	/// pool.shrink_to(1);
+	/// assert_eq!(pool.len(), 1);
+	/// assert_eq!(pool.capacity(), 69);
+	/// ```
+	pub fn shrink_to(&self, len: usize) {
+		self.inner.shrink_to(len);
+	}
+
+	/// The total [`RetainedSize`](crate::RetainedSize) of available objects in bytes, if the pool
+	/// has a [byte budget](PoolBuilder::max_retained_bytes) or is attached to a shared budget.
+	#[cfg_attr(
+		feature = "std",
+		doc = "\nShared budgets are created with [`PoolBudget`](crate::PoolBudget)."
+	)]
+	pub fn retained_bytes(&self) -> Option<usize> {
+		self.inner.retained.as_ref().map(Retained::bytes)
+	}
+
+	/// Drop available objects that were returned at least `older_than` ago, returning how many
+	/// were dropped.
+	///
+	/// See [`PoolBuilder::idle_timeout`] to drop old objects automatically.
+	#[cfg_attr(
+		feature = "std",
+		doc = "Objects in [thread caches](PoolBuilder::thread_cache) aren't checked."
+	)]
+	///
+	/// ```
+	/// # use dynamic_pooling::{ManualClock, Pool};
+	/// # use std::time::Duration;
+	/// let clock = ManualClock::new();
+	/// let pool = Pool::<String>::builder()
+	/// 	.capacity(69)
+	/// 	.clock(clock.clone())
+	/// 	.build()
+	/// 	.unwrap();
+	///
+	/// let foo = pool.take();
+	/// drop(pool.take());
+	/// clock.advance(Duration::from_secs(60));
+	/// drop(foo);
+	///
+	/// assert_eq!(pool.evict_idle(Duration::from_secs(30)), 1);
+	/// assert_eq!(pool.len(), 1);
+	/// ```
+	pub fn evict_idle(&self, older_than: Duration) -> usize {
+		self.inner.evict_idle(older_than, 0)
+	}
+
+	/// How long objects can be available before they're dropped, if set with
+	/// [`PoolBuilder::idle_timeout`].
+	pub fn idle_timeout(&self) -> Option<Duration> {
+		self.inner.idle_timeout
+	}
+
+	/// The maximum number of times an object is handed out, if set with
+	/// [`PoolBuilder::max_uses`].
+	pub fn max_uses(&self) -> Option<usize> {
+		self.inner.max_uses
+	}
+
+	/// How long objects are kept after they're created, if set with
+	/// [`PoolBuilder::max_lifetime`].
+	pub fn max_lifetime(&self) -> Option<Duration> {
+		self.inner.max_lifetime
+	}
+
+	/// The number of available objects a reaper keeps, set with [`PoolBuilder::min_idle`].
+	#[cfg_attr(
+		feature = "std",
+		doc = "\nReapers are started with [`Pool::spawn_reaper`]."
+	)]
+	pub fn min_idle(&self) -> usize {
+		self.inner.min_idle
+	}
+
+	/// A snapshot of the pool's statistics, if it was built with [`PoolBuilder::track_stats`].
+	///
+	/// See [`PoolStats`] for an example.
+	pub fn stats(&self) -> Option<PoolStats> {
+		let stats = self.inner.events.as_ref()?.stats.as_ref()?;
+		Some(stats.snapshot())
+	}
+
+	/// Set the pool's statistics back to `0`, except for
+	/// [`peak_in_use`](PoolStats::peak_in_use), which is set to the number of objects in use.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// let pool = Pool::<String>::builder().capacity(69).track_stats().build().unwrap();
+	/// let foo = pool.take();
+	/// drop(pool.take());
+	///
+	/// pool.reset_stats();
+	/// let stats = pool.stats().unwrap();
+	/// assert_eq!(stats.misses, 0);
+	/// assert_eq!(stats.returns, 0);
+	/// assert_eq!(stats.peak_in_use, 1);
+	/// ```
+	pub fn reset_stats(&self) {
+		if let Some(stats) = self
+			.inner
+			.events
+			.as_ref()
+			.and_then(|events| events.stats.as_ref())
+		{
+			stats.reset(self.in_use());
+		}
+	}
+
+	/// The maximum number of [live objects](Pool::live), if one was set with
+	/// [`PoolBuilder::max_live`].
+	pub fn max_live(&self) -> Option<usize> {
+		self.inner.max_live
+	}
+
+	/// The name of the pool, if one was set with [`PoolBuilder::name`].
+	pub fn name(&self) -> Option<&str> {
+		self.inner.name.as_deref()
 	}
 
 	/// Attach an object to the pool.
+	///
+	/// This counts as a live object, even if it exceeds the [maximum](Pool::max_live).
 	pub fn attach(&self, object: T) -> Pooled<T> {
-		Pooled::new(object, self)
+		self.inner.live.fetch_add(1, Ordering::Relaxed);
+		self.inner
+			.record(|events| events.attached(self.inner.in_use()));
+		let meta = ObjectMeta {
+			uses: 1,
+			fresh: true,
+			..ObjectMeta::new(self.inner.now())
+		};
+		Pooled::new(Entry { object, meta }, self)
+	}
+}
+
+impl<T: Reset> Debug for Pool<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Pool")
+			.field("name", &self.name())
+			.field("len", &self.len())
+			.field("in_use", &self.in_use())
+			.field("capacity", &self.capacity())
+			.finish()
 	}
 }
 
 /// This returns a reference to the same [`Pool`].
-impl<T: Default + Reset> Clone for Pool<T> {
+impl<T: Reset> Clone for Pool<T> {
 	fn clone(&self) -> Self {
 		Self {
 			inner: Arc::clone(&self.inner),
 		}
 	}
 }
+
+/// An error returned when a pool has reached its [maximum number of live objects](Pool::max_live).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PoolExhausted;
+
+impl Display for PoolExhausted {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str("pool has reached its maximum number of live objects")
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PoolExhausted {}
@@ -0,0 +1,108 @@
+use crate::PoolStats;
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Render the statistics of one or more named pools in the
+/// [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+///
+/// Each pool's metrics are labelled with its name.
+///
+/// ```
+/// # use dynamic_pooling::{prometheus_text, Pool};
+/// let pool = Pool::<String>::builder()
+/// 	.capacity(69)
+/// 	.name("strings")
+/// 	.track_stats()
+/// 	.build()
+/// 	.unwrap();
+/// drop(pool.take());
+/// drop(pool.take());
+///
+/// let stats = pool.stats().unwrap();
+/// let text = prometheus_text([(pool.name().unwrap(), stats)]);
+/// assert!(text.contains("# TYPE pool_hits_total counter\npool_hits_total{pool=\"strings\"} 1\n"));
+/// assert!(text.contains("pool_misses_total{pool=\"strings\"} 1\n"));
+/// assert!(text.contains("pool_other_discards_total{pool=\"strings\"} 0\n"));
+/// ```
+pub fn prometheus_text<'a>(pools: impl IntoIterator<Item = (&'a str, PoolStats)>) -> String {
+	let pools: Vec<_> = pools.into_iter().collect();
+	let mut text = String::new();
+	for metric in &METRICS {
+		let name = metric.name;
+		// writing to a string never fails
+		let _ = writeln!(text, "# HELP {name} {}", metric.help);
+		let _ = writeln!(text, "# TYPE {name} {}", metric.kind);
+		for (pool, stats) in &pools {
+			text.push_str(name);
+			text.push_str("{pool=\"");
+			escape_label(&mut text, pool);
+			let _ = writeln!(text, "\"}} {}", (metric.value)(stats));
+		}
+	}
+	text
+}
+
+/// A metric rendered by [`prometheus_text`].
+struct Metric {
+	name: &'static str,
+	kind: &'static str,
+	help: &'static str,
+	value: fn(&PoolStats) -> u64,
+}
+
+const METRICS: [Metric; 7] = [
+	Metric {
+		name: "pool_hits_total",
+		kind: "counter",
+		help: "Objects that were taken from the available objects.",
+		value: |stats| stats.hits,
+	},
+	Metric {
+		name: "pool_misses_total",
+		kind: "counter",
+		help: "Objects that were created because none were available.",
+		value: |stats| stats.misses,
+	},
+	Metric {
+		name: "pool_returns_total",
+		kind: "counter",
+		help: "Objects that were returned and kept by the pool.",
+		value: |stats| stats.returns,
+	},
+	Metric {
+		name: "pool_rejected_total",
+		kind: "counter",
+		help: "Objects that were dropped because the pool was full or over its byte budget when they were returned.",
+		value: |stats| stats.rejected,
+	},
+	Metric {
+		// not `pool_discards_total`, which the `metrics` feature uses for every reason
+		name: "pool_other_discards_total",
+		kind: "counter",
+		help: "Objects that were dropped for any other reason.",
+		value: |stats| stats.discards,
+	},
+	Metric {
+		name: "pool_detaches_total",
+		kind: "counter",
+		help: "Objects that were detached.",
+		value: |stats| stats.detaches,
+	},
+	Metric {
+		name: "pool_peak_in_use",
+		kind: "gauge",
+		help: "The most objects that were in use at once.",
+		value: |stats| stats.peak_in_use as u64,
+	},
+];
+
+fn escape_label(text: &mut String, value: &str) {
+	for c in value.chars() {
+		match c {
+			'\\' => text.push_str("\\\\"),
+			'"' => text.push_str("\\\""),
+			'\n' => text.push_str("\\n"),
+			c => text.push(c),
+		}
+	}
+}
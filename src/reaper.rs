@@ -0,0 +1,104 @@
+use crate::{Pool, Reset};
+use alloc::sync::Arc;
+use core::{
+	fmt::{self, Debug},
+	time::Duration,
+};
+use std::{
+	sync::mpsc::{self, RecvTimeoutError, Sender},
+	thread::{self, JoinHandle},
+};
+
+impl<T: Reset + Send + 'static> Pool<T> {
+	/// Spawn a thread that drops unused available objects every `interval`.
+	///
+	/// Objects are dropped once they've been available for the pool's
+	/// [idle timeout](crate::PoolBuilder::idle_timeout), or for the whole `interval` if there
+	/// isn't one. The reaper always keeps the pool's [minimum](crate::PoolBuilder::min_idle)
+	/// number of available objects, and can't drop objects in
+	/// [thread caches](crate::PoolBuilder::thread_cache).
+	///
+	/// The thread stops when every clone of the pool has been dropped, or when
+	/// [`Reaper::stop`] is called. Dropping the [`Reaper`] lets it keep running.
+	///
+	/// ```
+	/// # use dynamic_pooling::{ManualClock, Pool};
+	/// # use std::{thread, time::Duration};
+	/// # fn eventually(done: impl Fn() -> bool) -> bool {
+	/// # 	(0..10_000).any(|_| done() || {
+	/// # 		thread::sleep(Duration::from_millis(1));
+	/// # 		false
+	/// # 	})
+	/// # }
+	/// let clock = ManualClock::new();
+	/// let pool = Pool::<String>::builder()
+	/// 	.capacity(69)
+	/// 	.clock(clock.clone())
+	/// 	.idle_timeout(Duration::from_secs(60))
+	/// 	.min_idle(1)
+	/// 	.build()
+	/// 	.unwrap();
+	/// let reaper = pool.spawn_reaper(Duration::from_millis(1));
+	///
+	/// drop([pool.take(), pool.take(), pool.take()]);
+	/// clock.advance(Duration::from_secs(60));
+	/// assert!(eventually(|| pool.len() == 1));
+	///
+	/// // the reaper stops once the pool is gone
+	/// drop(pool);
+	/// assert!(eventually(|| reaper.is_finished()));
+	/// ```
+	///
+	/// # Panics
+	/// Panics if `interval` is zero, or if the thread can't be spawned.
+	pub fn spawn_reaper(&self, interval: Duration) -> Reaper {
+		assert!(!interval.is_zero(), "reaper interval must be more than 0");
+		let weak = Arc::downgrade(&self.inner);
+		let (stop, stopped) = mpsc::channel();
+		let thread = thread::Builder::new()
+			.name("dynamic-pooling-reaper".into())
+			.spawn(move || loop {
+				match stopped.recv_timeout(interval) {
+					Ok(()) => break,
+					Err(RecvTimeoutError::Timeout) => {},
+					// the reaper was dropped, so it can't be stopped anymore
+					Err(RecvTimeoutError::Disconnected) => thread::sleep(interval),
+				}
+				let Some(pool) = weak.upgrade() else {
+					break;
+				};
+				let older_than = pool.idle_timeout.unwrap_or(interval);
+				pool.evict_idle(older_than, pool.min_idle);
+			})
+			.expect("failed to spawn reaper thread");
+		Reaper { stop, thread }
+	}
+}
+
+/// A handle to a thread that drops unused objects, see [`Pool::spawn_reaper`].
+pub struct Reaper {
+	stop: Sender<()>,
+	thread: JoinHandle<()>,
+}
+
+impl Reaper {
+	/// Stop the thread and wait for it to finish.
+	pub fn stop(self) {
+		// this fails if the thread already stopped on its own
+		let _ = self.stop.send(());
+		let _ = self.thread.join();
+	}
+
+	/// Whether the thread has stopped.
+	pub fn is_finished(&self) -> bool {
+		self.thread.is_finished()
+	}
+}
+
+impl Debug for Reaper {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("Reaper")
+			.field("is_finished", &self.is_finished())
+			.finish()
+	}
+}
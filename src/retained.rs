@@ -0,0 +1,123 @@
+use alloc::{
+	boxed::Box,
+	collections::{BinaryHeap, VecDeque},
+	string::String,
+	vec::Vec,
+};
+use core::mem::size_of;
+#[cfg(feature = "std")]
+use std::{
+	collections::{HashMap, HashSet},
+	ffi::OsString,
+	path::PathBuf,
+};
+
+/// Trait for types that know how much memory they keep allocated.
+///
+/// This only needs to be an estimate, and usually doesn't include memory owned by the items of a
+/// collection.
+///
+/// ```
+/// # use dynamic_pooling::RetainedSize;
+/// struct Buffers {
+/// 	read: Vec<u8>,
+/// 	write: Vec<u8>,
+/// }
+///
+/// impl RetainedSize for Buffers {
+/// 	fn retained_size(&self) -> usize {
+/// 		self.read.retained_size() + self.write.retained_size()
+/// 	}
+/// }
+/// ```
+pub trait RetainedSize {
+	/// The number of bytes of memory this keeps allocated.
+	fn retained_size(&self) -> usize;
+}
+
+impl<T> RetainedSize for Box<T>
+where
+	T: RetainedSize,
+{
+	fn retained_size(&self) -> usize {
+		size_of::<T>() + RetainedSize::retained_size(&**self)
+	}
+}
+
+impl<T> RetainedSize for Vec<T> {
+	fn retained_size(&self) -> usize {
+		self.capacity() * size_of::<T>()
+	}
+}
+
+impl RetainedSize for String {
+	fn retained_size(&self) -> usize {
+		self.capacity()
+	}
+}
+
+impl<T> RetainedSize for VecDeque<T> {
+	fn retained_size(&self) -> usize {
+		self.capacity() * size_of::<T>()
+	}
+}
+
+impl<T> RetainedSize for BinaryHeap<T> {
+	fn retained_size(&self) -> usize {
+		self.capacity() * size_of::<T>()
+	}
+}
+
+#[cfg(feature = "std")]
+impl RetainedSize for PathBuf {
+	fn retained_size(&self) -> usize {
+		self.capacity()
+	}
+}
+
+#[cfg(feature = "std")]
+impl RetainedSize for OsString {
+	fn retained_size(&self) -> usize {
+		self.capacity()
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> RetainedSize for HashMap<K, V, S> {
+	fn retained_size(&self) -> usize {
+		self.capacity() * size_of::<(K, V)>()
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T, S> RetainedSize for HashSet<T, S> {
+	fn retained_size(&self) -> usize {
+		self.capacity() * size_of::<T>()
+	}
+}
+
+macro_rules! tuple_hell {
+	($(($($letter:ident:$number:tt),+$(,)?))+) => {$(
+		impl<$($letter),+> RetainedSize for ($($letter),+,) where $($letter: RetainedSize),+ {
+			fn retained_size(&self) -> usize {
+				0 $(+ self.$number.retained_size())+
+			}
+		}
+	)+}
+}
+
+tuple_hell! {
+	(A:0)
+	(A:0, B:1)
+	(A:0, B:1, C:2)
+	(A:0, B:1, C:2, D:3)
+	(A:0, B:1, C:2, D:3, E:4)
+	(A:0, B:1, C:2, D:3, E:4, F:5)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11)
+	(A:0, B:1, C:2, D:3, E:4, F:5, G:6, H:7, I:8, J:9, K:10, L:11, M:12)
+}
@@ -0,0 +1,166 @@
+use crate::{
+	storage::{retain_queue, Storage},
+	Pool, PoolBuilder, Reset, StorageKind,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+	fmt::{self, Debug},
+	ops::Deref,
+	sync::atomic::{AtomicUsize, Ordering},
+};
+use crossbeam_queue::{ArrayQueue, SegQueue};
+
+/// Used to spread threads across shards.
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+	/// The shard this thread uses first, before it's reduced to the number of shards.
+	static HOME_SHARD: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A pool that spreads its available objects across multiple queues to reduce contention.
+///
+/// Each thread takes from and returns to its own shard first, and steals from the other shards
+/// when its own is empty. This dereferences to a [`Pool`], and methods like [`Pool::len`] report
+/// the total across all shards.
+///
+/// This is the same as a pool built with [`StorageKind::Sharded`].
+///
+/// ```
+/// # use dynamic_pooling::ShardedPool;
+/// let pool = ShardedPool::<String>::new(4, 64);
+/// assert_eq!(pool.shards(), 4);
+/// assert_eq!(pool.capacity(), 64);
+///
+/// let objects = [pool.take(), pool.take(), pool.take()];
+/// assert_eq!(pool.in_use(), 3);
+/// drop(objects);
+/// assert_eq!(pool.len(), 3);
+/// ```
+pub struct ShardedPool<T: Reset> {
+	pool: Pool<T>,
+	shards: usize,
+}
+
+impl<T: Default + Reset> ShardedPool<T> {
+	/// Create a new pool with the specified number of shards and total capacity.
+	///
+	/// Note: The capacity will be fully allocated.
+	///
+	/// # Panics
+	/// Panics if the number of shards or the capacity is `0`.
+	pub fn new(shards: usize, capacity: usize) -> Self {
+		Self::from_builder(Pool::builder(), shards, capacity)
+	}
+}
+
+impl<T: Reset> ShardedPool<T> {
+	/// Create a new pool with the specified number of shards and total capacity, using `factory`
+	/// to create new objects.
+	///
+	/// Note: The capacity will be fully allocated.
+	///
+	/// # Panics
+	/// Panics if the number of shards or the capacity is `0`.
+	pub fn with_factory<F>(shards: usize, capacity: usize, factory: F) -> Self
+	where
+		F: Fn() -> T + Send + Sync + 'static,
+	{
+		Self::from_builder(PoolBuilder::new().factory(factory), shards, capacity)
+	}
+
+	fn from_builder(builder: PoolBuilder<T>, shards: usize, capacity: usize) -> Self {
+		let pool = builder
+			.capacity(capacity)
+			.storage(StorageKind::Sharded(shards))
+			.build()
+			.unwrap_or_else(|error| panic!("{error}"));
+		Self { pool, shards }
+	}
+
+	/// The number of shards.
+	pub fn shards(&self) -> usize {
+		self.shards
+	}
+
+	/// Get the underlying [`Pool`].
+	pub fn into_pool(self) -> Pool<T> {
+		self.pool
+	}
+}
+
+impl<T: Reset> Deref for ShardedPool<T> {
+	type Target = Pool<T>;
+	fn deref(&self) -> &Self::Target {
+		&self.pool
+	}
+}
+
+/// This returns a reference to the same [`ShardedPool`].
+impl<T: Reset> Clone for ShardedPool<T> {
+	fn clone(&self) -> Self {
+		Self {
+			pool: self.pool.clone(),
+			shards: self.shards,
+		}
+	}
+}
+
+impl<T: Reset> Debug for ShardedPool<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("ShardedPool")
+			.field("pool", &self.pool)
+			.field("shards", &self.shards)
+			.finish()
+	}
+}
+
+/// Storage for [`StorageKind::Sharded`].
+pub(super) struct Sharded<T> {
+	shards: Box<[ArrayQueue<T>]>,
+	/// Holds whatever doesn't fit in the shards after the capacity is raised.
+	overflow: SegQueue<T>,
+}
+
+impl<T> Sharded<T> {
+	/// The capacity is split evenly between the shards.
+	pub(super) fn new(shards: usize, capacity: usize) -> Self {
+		let shard_capacity = capacity.div_ceil(shards);
+		Self {
+			shards: (0..shards)
+				.map(|_| ArrayQueue::new(shard_capacity))
+				.collect(),
+			overflow: SegQueue::new(),
+		}
+	}
+
+	/// The shards in the order this thread should use them.
+	fn shards(&self) -> impl Iterator<Item = &ArrayQueue<T>> {
+		let home = HOME_SHARD.try_with(|shard| *shard).unwrap_or(0) % self.shards.len();
+		let (before, after) = self.shards.split_at(home);
+		after.iter().chain(before)
+	}
+}
+
+impl<T> Storage<T> for Sharded<T> {
+	fn push(&self, mut object: T) {
+		for shard in self.shards() {
+			match shard.push(object) {
+				Ok(()) => return,
+				Err(rejected) => object = rejected,
+			}
+		}
+		self.overflow.push(object);
+	}
+
+	fn pop(&self) -> Option<T> {
+		self.shards()
+			.find_map(ArrayQueue::pop)
+			.or_else(|| self.overflow.pop())
+	}
+
+	fn retain(&self, keep: impl FnMut(&T) -> bool) -> Vec<T> {
+		let len = self.shards.iter().map(ArrayQueue::len).sum::<usize>() + self.overflow.len();
+		retain_queue(len, || self.pop(), |object| self.push(object), keep)
+	}
+}
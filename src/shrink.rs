@@ -0,0 +1,138 @@
+use crate::Capacity;
+use alloc::{
+	collections::{BinaryHeap, VecDeque},
+	string::String,
+	vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::{
+	collections::{HashMap, HashSet},
+	ffi::OsString,
+	hash::{BuildHasher, Hash},
+	path::PathBuf,
+};
+
+/// Trait for types that can give back some of their allocated memory.
+///
+/// This is used by [`PoolBuilder::max_retained_capacity`](crate::PoolBuilder::max_retained_capacity).
+pub trait Shrink: Capacity {
+	/// Shrink the capacity, keeping at least `capacity`.
+	///
+	/// Like [`Vec::shrink_to`], the capacity may stay a bit larger than requested.
+	fn shrink_to(&mut self, capacity: usize);
+}
+
+/// What to do with returned objects that are over the
+/// [maximum retained capacity](crate::PoolBuilder::max_retained_capacity).
+///
+/// ```
+/// # use dynamic_pooling::{Oversized, Pool};
+/// let pool = Pool::<Vec<u8>>::builder()
+/// 	.capacity(69)
+/// 	.max_retained_capacity(1024, Oversized::Shrink)
+/// 	.build()
+/// 	.unwrap();
+///
+/// let mut vec = pool.take();
+/// vec.reserve(4096);
+/// drop(vec);
+/// assert_eq!(pool.len(), 1);
+///
+/// let vec = pool.take();
+/// assert!(vec.capacity() <= 1024);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Oversized {
+	/// [Shrink](Shrink::shrink_to) them to the maximum and keep them.
+	#[default]
+	Shrink,
+	/// Drop them instead of returning them to the pool.
+	Discard,
+}
+
+/// See [`PoolBuilder::max_retained_capacity`](crate::PoolBuilder::max_retained_capacity).
+pub(super) struct RetainLimit<T> {
+	pub(super) max: usize,
+	pub(super) oversized: Oversized,
+	/// These are function pointers so that [`PoolInner`](crate::pool::PoolInner) doesn't need
+	/// `T: Shrink`.
+	pub(super) capacity: fn(&T) -> usize,
+	pub(super) shrink: fn(&mut T, usize),
+}
+
+impl<T> RetainLimit<T> {
+	/// Returns `false` if the object should be dropped.
+	pub(super) fn apply(&self, object: &mut T) -> bool {
+		if (self.capacity)(object) <= self.max {
+			return true;
+		}
+		match self.oversized {
+			Oversized::Shrink => {
+				(self.shrink)(object, self.max);
+				true
+			},
+			Oversized::Discard => false,
+		}
+	}
+}
+
+impl<T> Shrink for Vec<T> {
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+impl Shrink for String {
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+impl<T> Shrink for VecDeque<T> {
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+impl<T: Ord> Shrink for BinaryHeap<T> {
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+#[cfg(feature = "std")]
+impl Shrink for PathBuf {
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+#[cfg(feature = "std")]
+impl Shrink for OsString {
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> Shrink for HashMap<K, V, S>
+where
+	K: Eq + Hash,
+	S: BuildHasher + Default,
+{
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T, S> Shrink for HashSet<T, S>
+where
+	T: Eq + Hash,
+	S: BuildHasher + Default,
+{
+	fn shrink_to(&mut self, capacity: usize) {
+		self.shrink_to(capacity);
+	}
+}
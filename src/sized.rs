@@ -0,0 +1,216 @@
+use crate::{
+	meta::Entry,
+	pool::Factory,
+	storage::{AnyStorage, Storage},
+	Capacity, Pool, PoolBuilder, Pooled, Reset,
+};
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+	fmt::{self, Debug},
+	ops::Deref,
+};
+use crossbeam_queue::SegQueue;
+
+/// A pool that sorts its available objects by capacity, so that objects that are already big
+/// enough can be taken with [`SizedPool::take_with_capacity`].
+///
+/// Objects are grouped into power-of-two size classes based on their capacity after being
+/// [`Reset`]. This dereferences to a [`Pool`], and [`Pool::take`] hands out the smallest available
+/// object.
+///
+/// Note: Room for available objects is only allocated as they're returned.
+///
+/// ```
+/// # use dynamic_pooling::SizedPool;
+/// let pool = SizedPool::<Vec<u8>>::new(69);
+///
+/// let small = pool.take_with_capacity(16);
+/// let big = pool.take_with_capacity(4096);
+/// let big_ptr = big.as_ptr();
+/// drop((small, big));
+///
+/// // the small buffer is skipped
+/// let buffer = pool.take_with_capacity(1000);
+/// assert_eq!(buffer.as_ptr(), big_ptr);
+/// assert_eq!(pool.len(), 1);
+/// ```
+pub struct SizedPool<T: Capacity + Reset> {
+	pool: Pool<T>,
+}
+
+impl<T: Capacity + Reset> SizedPool<T> {
+	/// Create a new pool with the specified capacity.
+	///
+	/// New objects are created with [`Capacity::with_capacity`].
+	///
+	/// # Panics
+	/// Panics if the capacity is `0`.
+	pub fn new(capacity: usize) -> Self {
+		let pool = PoolBuilder {
+			factory: Some(Factory::Fn(empty)),
+			size_classes: Some(T::capacity),
+			..PoolBuilder::new()
+		}
+		.capacity(capacity)
+		.build()
+		.unwrap_or_else(|error| panic!("{error}"));
+		Self { pool }
+	}
+
+	/// Take an object with a capacity of at least `min` from the pool, creating a new one if none
+	/// are available.
+	///
+	/// New objects are created with `min` rounded up to a power of two, so that they can be reused
+	/// for similar sizes. If `min` is `0`, they're created empty.
+	///
+	/// ```
+	/// # use dynamic_pooling::SizedPool;
+	/// let pool = SizedPool::<Vec<u8>>::new(69);
+	///
+	/// assert_eq!(pool.take_with_capacity(0).capacity(), 0);
+	/// assert_eq!(pool.take_with_capacity(1000).capacity(), 1024);
+	///
+	/// // objects that are big enough are reused, even if their capacity isn't a power of two
+	/// let mut buffer = pool.take();
+	/// buffer.reserve_exact(1000);
+	/// let capacity = buffer.capacity();
+	/// drop(buffer);
+	/// assert_eq!(pool.try_take_with_capacity(capacity).unwrap().capacity(), capacity);
+	/// ```
+	pub fn take_with_capacity(&self, min: usize) -> Pooled<T> {
+		let inner = &self.pool.inner;
+		let entry = inner
+			.pop_or_create_with(
+				|| inner.pop_stored_with(|storage| classes(storage).pop_at_least(min)),
+				|| match min {
+					0 => empty(),
+					min => T::with_capacity(min.checked_next_power_of_two().unwrap_or(min)),
+				},
+			)
+			.expect("sized pools have no maximum number of live objects");
+		Pooled::new(entry, &self.pool)
+	}
+
+	/// Take an object with a capacity of at least `min` from the pool, returning [`None`] if none
+	/// are available.
+	///
+	/// This will never allocate.
+	///
+	/// ```
+	/// # use dynamic_pooling::SizedPool;
+	/// let pool = SizedPool::<String>::new(69);
+	/// drop(pool.take_with_capacity(10));
+	///
+	/// assert!(pool.try_take_with_capacity(100).is_none());
+	/// assert!(pool.try_take_with_capacity(10).is_some());
+	/// ```
+	pub fn try_take_with_capacity(&self, min: usize) -> Option<Pooled<T>> {
+		let inner = &self.pool.inner;
+		let entry = inner.pop_stored_with(|storage| classes(storage).pop_at_least(min))?;
+		Some(Pooled::new(inner.hand_out(entry), &self.pool))
+	}
+
+	/// Get the underlying [`Pool`].
+	pub fn into_pool(self) -> Pool<T> {
+		self.pool
+	}
+}
+
+/// The factory for [`SizedPool::new`].
+fn empty<T: Capacity>() -> T {
+	T::with_capacity(0)
+}
+
+fn classes<T>(storage: &AnyStorage<T>) -> &Classes<T> {
+	match storage {
+		AnyStorage::Classes(classes) => classes,
+		_ => unreachable!("sized pools always use size classes"),
+	}
+}
+
+impl<T: Capacity + Reset> Deref for SizedPool<T> {
+	type Target = Pool<T>;
+	fn deref(&self) -> &Self::Target {
+		&self.pool
+	}
+}
+
+/// This returns a reference to the same [`SizedPool`].
+impl<T: Capacity + Reset> Clone for SizedPool<T> {
+	fn clone(&self) -> Self {
+		Self {
+			pool: self.pool.clone(),
+		}
+	}
+}
+
+impl<T: Capacity + Reset> Debug for SizedPool<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("SizedPool")
+			.field("pool", &self.pool)
+			.finish()
+	}
+}
+
+/// Storage for [`SizedPool`], with a queue for each power-of-two size class.
+///
+/// Class `0` holds objects with no capacity, and class `n` holds capacities from `2^(n - 1)` up to
+/// `2^n - 1`.
+pub(super) struct Classes<T> {
+	classes: Box<[SegQueue<Entry<T>>]>,
+	capacity_of: fn(&T) -> usize,
+}
+
+impl<T> Classes<T> {
+	pub(super) fn new(capacity_of: fn(&T) -> usize) -> Self {
+		Self {
+			classes: (0..=usize::BITS).map(|_| SegQueue::new()).collect(),
+			capacity_of,
+		}
+	}
+
+	/// Take one of the smallest objects with a capacity of at least `min`.
+	pub(super) fn pop_at_least(&self, min: usize) -> Option<Entry<T>> {
+		let first = class_of(min);
+		// objects in the class that `min` falls in may still be too small
+		let class = &self.classes[first];
+		let mut too_small = Vec::new();
+		let mut found = None;
+		for _ in 0..class.len() {
+			let Some(idle) = class.pop() else {
+				break;
+			};
+			if (self.capacity_of)(&idle.object) >= min {
+				found = Some(idle);
+				break;
+			}
+			too_small.push(idle);
+		}
+		for idle in too_small {
+			class.push(idle);
+		}
+		// every object in the larger classes is big enough
+		found.or_else(|| self.classes[first + 1..].iter().find_map(SegQueue::pop))
+	}
+}
+
+fn class_of(capacity: usize) -> usize {
+	(usize::BITS - capacity.leading_zeros()) as usize
+}
+
+impl<T> Storage<Entry<T>> for Classes<T> {
+	fn push(&self, idle: Entry<T>) {
+		self.classes[class_of((self.capacity_of)(&idle.object))].push(idle);
+	}
+
+	fn pop(&self) -> Option<Entry<T>> {
+		self.pop_at_least(0)
+	}
+
+	fn retain(&self, mut keep: impl FnMut(&Entry<T>) -> bool) -> Vec<Entry<T>> {
+		self.classes
+			.iter()
+			.flat_map(|class| Storage::retain(class, &mut keep))
+			.collect()
+	}
+}
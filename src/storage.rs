@@ -0,0 +1,220 @@
+use crate::{meta::Entry, sized::Classes};
+#[cfg(feature = "std")]
+use crate::{sharded::Sharded, wait::lock};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::mem;
+use crossbeam_queue::{ArrayQueue, SegQueue};
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+/// How a pool stores its available objects.
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// # use dynamic_pooling::{Pool, StorageKind};
+/// let pool = Pool::<String>::builder()
+/// 	.capacity(69)
+/// 	.storage(StorageKind::Lifo)
+/// 	.build()
+/// 	.unwrap();
+///
+/// let mut foo = pool.take();
+/// let mut bar = pool.take();
+/// foo.push_str("foo");
+/// bar.push_str("bar");
+/// let (foo_ptr, bar_ptr) = (foo.as_ptr(), bar.as_ptr());
+/// drop((foo, bar));
+///
+/// // the most recently returned object comes out first
+/// assert_eq!(pool.take().as_ptr(), bar_ptr);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StorageKind {
+	/// A first-in first-out queue that's allocated up front.
+	///
+	/// This is lock-free, but hands out the object that was returned the longest time ago, which
+	/// is the least likely to still be in the CPU cache.
+	#[default]
+	Fifo,
+	/// A first-in first-out queue that only allocates room for objects as they're returned.
+	LazyFifo,
+	/// A last-in first-out stack that only allocates room for objects as they're returned.
+	///
+	/// This hands out the object that was returned most recently, which is the most likely to
+	/// still be in the CPU cache, but it uses a lock.
+	#[cfg(feature = "std")]
+	Lifo,
+	/// This many first-in first-out queues that are allocated up front, where each thread uses its
+	/// own queue first.
+	///
+	/// This reduces contention when many threads use the pool at once. See
+	/// [`ShardedPool`](crate::ShardedPool).
+	#[cfg(feature = "std")]
+	Sharded(usize),
+}
+
+/// Where a pool keeps its available objects.
+///
+/// Implementations don't need to enforce the capacity, since the pool does that itself.
+pub(super) trait Storage<T> {
+	fn push(&self, object: T);
+	fn pop(&self) -> Option<T>;
+	/// Remove the objects that `keep` returns `false` for.
+	///
+	/// They're returned instead of dropped, since dropping them can run any code.
+	fn retain(&self, keep: impl FnMut(&T) -> bool) -> Vec<T>;
+}
+
+/// Implements [`Storage::retain`] for queues by cycling through the first `len` objects.
+pub(super) fn retain_queue<T>(
+	len: usize,
+	mut pop: impl FnMut() -> Option<T>,
+	mut push: impl FnMut(T),
+	mut keep: impl FnMut(&T) -> bool,
+) -> Vec<T> {
+	let mut removed = Vec::new();
+	for _ in 0..len {
+		let Some(object) = pop() else {
+			break;
+		};
+		if keep(&object) {
+			push(object);
+		} else {
+			removed.push(object);
+		}
+	}
+	removed
+}
+
+/// A queue that's allocated up front, which spills into a [`SegQueue`] if the capacity is raised.
+pub(super) struct Fifo<T> {
+	queue: ArrayQueue<T>,
+	overflow: SegQueue<T>,
+}
+
+impl<T> Storage<T> for Fifo<T> {
+	fn push(&self, object: T) {
+		if let Err(object) = self.queue.push(object) {
+			self.overflow.push(object);
+		}
+	}
+
+	fn pop(&self) -> Option<T> {
+		self.queue.pop().or_else(|| self.overflow.pop())
+	}
+
+	fn retain(&self, keep: impl FnMut(&T) -> bool) -> Vec<T> {
+		let len = self.queue.len() + self.overflow.len();
+		retain_queue(len, || self.pop(), |object| self.push(object), keep)
+	}
+}
+
+impl<T> Storage<T> for SegQueue<T> {
+	fn push(&self, object: T) {
+		SegQueue::push(self, object);
+	}
+
+	fn pop(&self) -> Option<T> {
+		SegQueue::pop(self)
+	}
+
+	fn retain(&self, keep: impl FnMut(&T) -> bool) -> Vec<T> {
+		retain_queue(self.len(), || self.pop(), |object| self.push(object), keep)
+	}
+}
+
+#[cfg(feature = "std")]
+pub(super) struct Lifo<T>(Mutex<Vec<T>>);
+
+#[cfg(feature = "std")]
+impl<T> Storage<T> for Lifo<T> {
+	fn push(&self, object: T) {
+		lock(&self.0).push(object);
+	}
+
+	fn pop(&self) -> Option<T> {
+		lock(&self.0).pop()
+	}
+
+	fn retain(&self, mut keep: impl FnMut(&T) -> bool) -> Vec<T> {
+		let mut objects = lock(&self.0);
+		let (kept, removed) = mem::take(&mut *objects)
+			.into_iter()
+			.partition(|object| keep(object));
+		*objects = kept;
+		removed
+	}
+}
+
+/// Any of the storage backends, picked with a [`StorageKind`].
+// there's only one of these per pool, so its size doesn't matter
+#[allow(clippy::large_enum_variant)]
+pub(super) enum AnyStorage<T> {
+	Fifo(Fifo<Entry<T>>),
+	LazyFifo(SegQueue<Entry<T>>),
+	#[cfg(feature = "std")]
+	Lifo(Lifo<Entry<T>>),
+	#[cfg(feature = "std")]
+	Sharded(Sharded<Entry<T>>),
+	/// Used by [`SizedPool`](crate::SizedPool), so it has no [`StorageKind`].
+	Classes(Classes<T>),
+}
+
+impl<T> AnyStorage<T> {
+	/// `capacity` is only allocated up front for [`StorageKind::Fifo`] and
+	/// [`StorageKind::Sharded`].
+	pub(super) fn new(kind: StorageKind, capacity: usize) -> Self {
+		match kind {
+			StorageKind::Fifo => Self::Fifo(Fifo {
+				queue: ArrayQueue::new(capacity),
+				overflow: SegQueue::new(),
+			}),
+			StorageKind::LazyFifo => Self::LazyFifo(SegQueue::new()),
+			#[cfg(feature = "std")]
+			StorageKind::Lifo => Self::Lifo(Lifo(Mutex::new(Vec::new()))),
+			#[cfg(feature = "std")]
+			StorageKind::Sharded(shards) => Self::Sharded(Sharded::new(shards, capacity)),
+		}
+	}
+}
+
+impl<T> Storage<Entry<T>> for AnyStorage<T> {
+	fn push(&self, object: Entry<T>) {
+		match self {
+			Self::Fifo(storage) => storage.push(object),
+			Self::LazyFifo(storage) => Storage::push(storage, object),
+			#[cfg(feature = "std")]
+			Self::Lifo(storage) => storage.push(object),
+			#[cfg(feature = "std")]
+			Self::Sharded(storage) => storage.push(object),
+			Self::Classes(storage) => storage.push(object),
+		}
+	}
+
+	fn pop(&self) -> Option<Entry<T>> {
+		match self {
+			Self::Fifo(storage) => storage.pop(),
+			Self::LazyFifo(storage) => Storage::pop(storage),
+			#[cfg(feature = "std")]
+			Self::Lifo(storage) => storage.pop(),
+			#[cfg(feature = "std")]
+			Self::Sharded(storage) => storage.pop(),
+			Self::Classes(storage) => storage.pop(),
+		}
+	}
+
+	fn retain(&self, keep: impl FnMut(&Entry<T>) -> bool) -> Vec<Entry<T>> {
+		match self {
+			Self::Fifo(storage) => storage.retain(keep),
+			Self::LazyFifo(storage) => Storage::retain(storage, keep),
+			#[cfg(feature = "std")]
+			Self::Lifo(storage) => storage.retain(keep),
+			#[cfg(feature = "std")]
+			Self::Sharded(storage) => storage.retain(keep),
+			Self::Classes(storage) => storage.retain(keep),
+		}
+	}
+}
@@ -0,0 +1,144 @@
+use ::tracing::{debug, error};
+use alloc::string::String;
+
+/// Emits events for a pool with the `tracing` feature.
+pub(super) struct Tracing {
+	pool: Option<String>,
+	type_name: &'static str,
+}
+
+impl Tracing {
+	pub(super) fn new(pool: Option<&str>, type_name: &'static str) -> Self {
+		Self {
+			pool: pool.map(String::from),
+			type_name,
+		}
+	}
+
+	pub(super) fn created(&self) {
+		debug!(
+			pool = self.pool.as_deref(),
+			type_name = self.type_name,
+			"created an object because none were available",
+		);
+	}
+
+	pub(super) fn rejected(&self) {
+		debug!(
+			pool = self.pool.as_deref(),
+			type_name = self.type_name,
+			"dropped a returned object because the pool was full",
+		);
+	}
+
+	pub(super) fn orphaned(&self) {
+		debug!(
+			pool = self.pool.as_deref(),
+			type_name = self.type_name,
+			"dropped an object because its pool was dropped",
+		);
+	}
+
+	pub(super) fn reset_panicked(&self) {
+		error!(
+			pool = self.pool.as_deref(),
+			type_name = self.type_name,
+			"an object panicked while being reset",
+		);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Pool, PoolBuilder};
+	use std::{
+		io,
+		panic::{self, AssertUnwindSafe},
+		sync::{Arc, Mutex},
+	};
+	use tracing::Level;
+
+	/// Where captured events are written.
+	#[derive(Clone, Default)]
+	struct Output(Arc<Mutex<Vec<u8>>>);
+
+	impl io::Write for Output {
+		fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+			self.0.lock().unwrap().extend_from_slice(bytes);
+			Ok(bytes.len())
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			Ok(())
+		}
+	}
+
+	/// Capture the events emitted by `f`, one per line.
+	fn capture(f: impl FnOnce()) -> Vec<String> {
+		let output = Output::default();
+		let writer = output.clone();
+		let subscriber = tracing_subscriber::fmt()
+			.with_max_level(Level::DEBUG)
+			.with_writer(move || writer.clone())
+			.without_time()
+			.finish();
+		tracing::subscriber::with_default(subscriber, f);
+		let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+		output.lines().map(String::from).collect()
+	}
+
+	#[test]
+	fn emits_lifecycle_events() {
+		let events = capture(|| {
+			let pool = Pool::<String>::builder()
+				.capacity(1)
+				.name("strings")
+				.build()
+				.unwrap();
+			drop([pool.take(), pool.take()]);
+			let foo = pool.take();
+			drop(pool);
+			drop(foo);
+		});
+
+		let fields = r#"pool="strings" type_name="alloc::string::String""#;
+		let expected = [
+			"created an object because none were available",
+			"created an object because none were available",
+			"dropped a returned object because the pool was full",
+			"dropped an object because its pool was dropped",
+		];
+		assert_eq!(events.len(), expected.len(), "{events:#?}");
+		for (event, message) in events.iter().zip(expected) {
+			assert!(event.contains(message), "{event}");
+			assert!(event.contains(fields), "{event}");
+		}
+	}
+
+	#[test]
+	fn emits_reset_panics() {
+		let events = capture(|| {
+			let pool = PoolBuilder::new()
+				.capacity(1)
+				.factory(String::new)
+				.reset(|_| panic!("oops"))
+				.build()
+				.unwrap();
+			let foo = pool.take();
+			let result = panic::catch_unwind(AssertUnwindSafe(|| drop(foo)));
+			assert!(result.is_err());
+		});
+
+		let last = events.last().expect("an event");
+		assert!(last.contains("ERROR"), "{last}");
+		assert!(
+			last.contains("an object panicked while being reset"),
+			"{last}"
+		);
+		assert!(
+			last.contains(r#"type_name="alloc::string::String""#),
+			"{last}"
+		);
+		assert!(!last.contains("pool="), "{last}");
+	}
+}
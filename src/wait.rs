@@ -0,0 +1,397 @@
+use crate::{Pool, PoolExhausted, Pooled, Reset};
+use alloc::{collections::VecDeque, sync::Arc};
+use core::{
+	fmt::{self, Debug},
+	future::Future,
+	pin::Pin,
+	sync::atomic::{self, AtomicBool, AtomicUsize, Ordering},
+	task::{Context, Poll, Waker},
+	time::Duration,
+};
+use std::{
+	sync::{Mutex, MutexGuard},
+	thread::{self, Thread},
+	time::Instant,
+};
+
+/// A first-in first-out queue of things waiting for an object to be returned.
+pub(super) struct Waiters {
+	/// The length of the queue, so that returning objects doesn't need to lock it.
+	len: AtomicUsize,
+	queue: Mutex<VecDeque<Arc<Waiter>>>,
+}
+
+pub(super) struct Waiter {
+	notified: AtomicBool,
+	wake: Wake,
+}
+
+/// How to wake up a [`Waiter`].
+enum Wake {
+	Thread(Thread),
+	/// The waker is replaced whenever the future is polled again.
+	Task(Mutex<Waker>),
+}
+
+impl Waiters {
+	pub(super) fn new() -> Self {
+		Self {
+			len: AtomicUsize::new(0),
+			queue: Mutex::new(VecDeque::new()),
+		}
+	}
+
+	/// Add a waiter to the back of the queue, or the front if it already waited its turn.
+	fn push(&self, waiter: &Arc<Waiter>, front: bool) {
+		let mut queue = lock(&self.queue);
+		if front {
+			queue.push_front(Arc::clone(waiter));
+		} else {
+			queue.push_back(Arc::clone(waiter));
+		}
+		self.len.store(queue.len(), Ordering::SeqCst);
+		// pairs with the fence in `notify_one`, so either the waiter sees the returned object when
+		// it checks again, or the returning thread sees the waiter
+		atomic::fence(Ordering::SeqCst);
+	}
+
+	/// Remove a waiter that's giving up.
+	///
+	/// If it was already notified, the notification is passed on so it doesn't get lost.
+	fn cancel(&self, waiter: &Arc<Waiter>) {
+		let mut queue = lock(&self.queue);
+		match queue.iter().position(|other| Arc::ptr_eq(other, waiter)) {
+			Some(index) => {
+				queue.remove(index);
+				self.len.store(queue.len(), Ordering::SeqCst);
+			},
+			None => {
+				drop(queue);
+				self.notify_one();
+			},
+		}
+	}
+
+	/// Wake the waiter at the front of the queue, if there is one.
+	pub(super) fn notify_one(&self) {
+		// the object being returned may have been stored with `Relaxed`, so this keeps the load
+		// below from happening before it
+		atomic::fence(Ordering::SeqCst);
+		if self.len.load(Ordering::SeqCst) == 0 {
+			return;
+		}
+		let mut queue = lock(&self.queue);
+		let waiter = queue.pop_front();
+		self.len.store(queue.len(), Ordering::SeqCst);
+		drop(queue);
+
+		if let Some(waiter) = waiter {
+			waiter.notified.store(true, Ordering::SeqCst);
+			match &waiter.wake {
+				Wake::Thread(thread) => thread.unpark(),
+				Wake::Task(waker) => lock(waker).wake_by_ref(),
+			}
+		}
+	}
+}
+
+impl Waiter {
+	fn new(wake: Wake) -> Self {
+		Self {
+			notified: AtomicBool::new(false),
+			wake,
+		}
+	}
+
+	/// Park until notified, returning `false` if the deadline passed first.
+	fn wait(&self, deadline: Option<Instant>) -> bool {
+		loop {
+			if self.notified.swap(false, Ordering::SeqCst) {
+				return true;
+			}
+			match deadline {
+				None => thread::park(),
+				Some(deadline) => {
+					let now = Instant::now();
+					if now >= deadline {
+						return false;
+					}
+					thread::park_timeout(deadline - now);
+				},
+			}
+		}
+	}
+}
+
+/// Lock a mutex, ignoring poisoning since nothing here can panic while holding a lock.
+pub(super) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+	mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+impl<T: Reset> Pool<T> {
+	/// Take an object from the pool, blocking until one is returned if the pool has reached its
+	/// [maximum number of live objects](Pool::max_live).
+	///
+	/// Waiting threads are woken up in the order they started waiting.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// # use std::{thread, time::Duration};
+	/// let pool = Pool::<String>::builder().capacity(69).max_live(1).build().unwrap();
+	/// let foo = pool.take();
+	///
+	/// thread::spawn(move || {
+	/// 	thread::sleep(Duration::from_millis(10));
+	/// 	drop(foo);
+	/// });
+	///
+	/// let foo = pool.take_blocking();
+	/// ```
+	pub fn take_blocking(&self) -> Pooled<T> {
+		self.take_until(None).expect("no deadline")
+	}
+
+	/// Like [`Pool::take_blocking`], but gives up after the timeout.
+	///
+	/// ```
+	/// # use dynamic_pooling::{Pool, PoolExhausted};
+	/// # use std::time::Duration;
+	/// let pool = Pool::<String>::builder().capacity(69).max_live(1).build().unwrap();
+	/// let foo = pool.take();
+	///
+	/// let result = pool.take_timeout(Duration::from_millis(10));
+	/// assert_eq!(result.err(), Some(PoolExhausted));
+	///
+	/// drop(foo);
+	/// assert!(pool.take_timeout(Duration::from_millis(10)).is_ok());
+	/// ```
+	pub fn take_timeout(&self, timeout: Duration) -> Result<Pooled<T>, PoolExhausted> {
+		self.take_until(Instant::now().checked_add(timeout))
+	}
+
+	/// Take an object from the pool, waiting until one is returned if the pool has reached its
+	/// [maximum number of live objects](Pool::max_live).
+	///
+	/// This works with any async runtime. Waiting tasks are woken up in the order they started
+	/// waiting, and dropping the future before it completes won't lose any objects.
+	///
+	/// ```
+	/// # use dynamic_pooling::Pool;
+	/// # use std::{future::Future, pin::pin, sync::Arc, task::{Context, Poll, Wake}, thread};
+	/// # struct Unpark(thread::Thread);
+	/// # impl Wake for Unpark {
+	/// # 	fn wake(self: Arc<Self>) {
+	/// # 		self.0.unpark();
+	/// # 	}
+	/// # }
+	/// # fn block_on<F: Future>(future: F) -> F::Output {
+	/// # 	let mut future = pin!(future);
+	/// # 	let waker = Arc::new(Unpark(thread::current())).into();
+	/// # 	loop {
+	/// # 		match future.as_mut().poll(&mut Context::from_waker(&waker)) {
+	/// # 			Poll::Ready(output) => return output,
+	/// # 			Poll::Pending => thread::park(),
+	/// # 		}
+	/// # 	}
+	/// # }
+	/// # block_on(async {
+	/// let pool = Pool::<String>::builder().capacity(69).max_live(1).build().unwrap();
+	/// let foo = pool.take_async().await;
+	///
+	/// thread::spawn(move || drop(foo));
+	///
+	/// let foo = pool.take_async().await;
+	/// # });
+	/// ```
+	pub fn take_async(&self) -> TakeAsync<'_, T> {
+		TakeAsync {
+			pool: self,
+			waiter: None,
+			front: false,
+		}
+	}
+
+	fn take_until(&self, deadline: Option<Instant>) -> Result<Pooled<T>, PoolExhausted> {
+		if let Ok(object) = self.try_take_bounded() {
+			return Ok(object);
+		}
+
+		let waiters = &self.inner.waiters;
+		let waiter = Arc::new(Waiter::new(Wake::Thread(thread::current())));
+		let mut front = false;
+		loop {
+			waiters.push(&waiter, front);
+			// an object may have been returned before we were queued
+			if let Ok(object) = self.try_take_bounded() {
+				waiters.cancel(&waiter);
+				return Ok(object);
+			}
+			if !waiter.wait(deadline) {
+				waiters.cancel(&waiter);
+				return Err(PoolExhausted);
+			}
+			if let Ok(object) = self.try_take_bounded() {
+				return Ok(object);
+			}
+			// someone else got to it first, so keep our place in line
+			front = true;
+		}
+	}
+}
+
+/// The future returned by [`Pool::take_async`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TakeAsync<'a, T: Reset> {
+	pool: &'a Pool<T>,
+	/// Set while this future is in the queue of waiters.
+	waiter: Option<Arc<Waiter>>,
+	/// Whether this future has already waited its turn.
+	front: bool,
+}
+
+impl<T: Reset> Future for TakeAsync<'_, T> {
+	type Output = Pooled<T>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = &mut *self;
+		let waiters = &this.pool.inner.waiters;
+
+		if let Some(waiter) = &this.waiter {
+			let Wake::Task(waker) = &waiter.wake else {
+				unreachable!("always a task")
+			};
+			lock(waker).clone_from(cx.waker());
+			// check after replacing the waker, so a notification can't go to the old one
+			if !waiter.notified.swap(false, Ordering::SeqCst) {
+				return Poll::Pending;
+			}
+			// notifying a waiter removes it from the queue
+			this.waiter = None;
+			this.front = true;
+		}
+
+		if let Ok(object) = this.pool.try_take_bounded() {
+			return Poll::Ready(object);
+		}
+
+		let waiter = Arc::new(Waiter::new(Wake::Task(Mutex::new(cx.waker().clone()))));
+		waiters.push(&waiter, this.front);
+		// an object may have been returned before we were queued
+		if let Ok(object) = this.pool.try_take_bounded() {
+			waiters.cancel(&waiter);
+			return Poll::Ready(object);
+		}
+		this.waiter = Some(waiter);
+		Poll::Pending
+	}
+}
+
+impl<T: Reset> Drop for TakeAsync<'_, T> {
+	fn drop(&mut self) {
+		if let Some(waiter) = &self.waiter {
+			self.pool.inner.waiters.cancel(waiter);
+		}
+	}
+}
+
+impl<T: Reset> Debug for TakeAsync<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.debug_struct("TakeAsync")
+			.field("pool", self.pool)
+			.field("waiting", &self.waiter.is_some())
+			.finish()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Pool;
+	use std::{
+		future::Future,
+		sync::{
+			atomic::{AtomicBool, Ordering},
+			mpsc, Arc,
+		},
+		task::{Context, Poll, Wake, Waker},
+		thread,
+	};
+
+	/// A waker that remembers whether it was woken.
+	#[derive(Default)]
+	struct Flag(AtomicBool);
+
+	impl Wake for Flag {
+		fn wake(self: Arc<Self>) {
+			self.0.store(true, Ordering::SeqCst);
+		}
+	}
+
+	impl Flag {
+		fn take(&self) -> bool {
+			self.0.swap(false, Ordering::SeqCst)
+		}
+	}
+
+	fn bounded_pool() -> Pool<String> {
+		Pool::builder().capacity(69).max_live(1).build().unwrap()
+	}
+
+	fn wait_for_waiters(pool: &Pool<String>, len: usize) {
+		while pool.inner.waiters.len.load(Ordering::SeqCst) < len {
+			thread::yield_now();
+		}
+	}
+
+	#[test]
+	fn dropped_future_passes_on_its_object() {
+		let pool = bounded_pool();
+		let foo = pool.take();
+
+		let first_flag = Arc::new(Flag::default());
+		let second_flag = Arc::new(Flag::default());
+		let first_waker = Waker::from(Arc::clone(&first_flag));
+		let second_waker = Waker::from(Arc::clone(&second_flag));
+		let mut first = Box::pin(pool.take_async());
+		let mut second = Box::pin(pool.take_async());
+		let mut first_cx = Context::from_waker(&first_waker);
+		let mut second_cx = Context::from_waker(&second_waker);
+		assert!(first.as_mut().poll(&mut first_cx).is_pending());
+		assert!(second.as_mut().poll(&mut second_cx).is_pending());
+
+		drop(foo);
+		assert!(first_flag.take());
+		assert!(!second_flag.take());
+
+		// the first future was notified, but is dropped before taking the object
+		drop(first);
+		assert!(second_flag.take());
+		assert!(matches!(
+			second.as_mut().poll(&mut second_cx),
+			Poll::Ready(_)
+		));
+	}
+
+	#[test]
+	fn blocking_takes_are_first_in_first_out() {
+		let pool = bounded_pool();
+		let foo = pool.take();
+		let (order, received) = mpsc::channel();
+
+		let mut threads = Vec::new();
+		for (index, name) in ["first", "second"].into_iter().enumerate() {
+			let (waiting_pool, order) = (pool.clone(), order.clone());
+			threads.push(thread::spawn(move || {
+				let object = waiting_pool.take_blocking();
+				order.send(name).unwrap();
+				drop(object);
+			}));
+			wait_for_waiters(&pool, index + 1);
+		}
+
+		drop(foo);
+		for thread in threads {
+			thread.join().unwrap();
+		}
+		assert_eq!(received.try_iter().collect::<Vec<_>>(), ["first", "second"]);
+	}
+}